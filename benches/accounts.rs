@@ -0,0 +1,55 @@
+//!Compares memory and construction time of 100k accounts with owned and shared keys.
+//!
+//!Run with `cargo bench`.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::mem;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use otpshka::{Algorithm, HOTP, TOTP, TotpRef};
+
+const SECRET: [u8; 20] = *b"12345678901234567890";
+const ACCOUNTS: usize = 100_000;
+
+struct CountingAlloc;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn measure<T, F: FnOnce() -> Vec<T>>(name: &str, cb: F) {
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    let start = Instant::now();
+    let accounts = black_box(cb());
+    let elapsed: Duration = start.elapsed();
+    let heap = ALLOCATED.load(Ordering::Relaxed) - before;
+
+    println!("{:>10}: {:>4} bytes per TOTP, {:>6} KiB heap total, built in {:>9?}", name, mem::size_of::<T>(), heap / 1024, elapsed);
+    drop(accounts);
+}
+
+fn main() {
+    measure("owned", || (0..ACCOUNTS).map(|_| TOTP::new(Algorithm::SHA1, SECRET)).collect::<Vec<TOTP>>());
+
+    let shared = Arc::new(HOTP::new(Algorithm::SHA1, SECRET));
+    measure("arc", || (0..ACCOUNTS).map(|_| TOTP::from_hotp(shared.clone())).collect::<Vec<TOTP<Arc<HOTP>>>>());
+
+    let shared = HOTP::new(Algorithm::SHA1, SECRET);
+    measure("reference", || (0..ACCOUNTS).map(|_| TotpRef::from_hotp(&shared)).collect::<Vec<TotpRef>>());
+}
@@ -0,0 +1,102 @@
+//!Compares safe truncation and formatting of `HOTP::generate_to` against the previous `unsafe` implementation.
+//!
+//!Previous implementation is reproduced here: pointer based truncation and `write!` into raw buffer.
+//!Run with `cargo bench`.
+
+use std::fmt::{self, Write};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+use std::{mem, ptr};
+
+use otpshka::{Algorithm, HOTP};
+
+const SECRET: [u8; 20] = *b"12345678901234567890";
+const ITERATIONS: u64 = 1_000_000;
+
+fn measure<F: FnMut(u64)>(mut cb: F) -> Duration {
+    let start = Instant::now();
+    for counter in 0..ITERATIONS {
+        cb(black_box(counter));
+    }
+    start.elapsed() / ITERATIONS as u32
+}
+
+fn safe_truncate(sign: &[u8], digits: u8) -> u32 {
+    let offset = (sign[sign.len() - 1] & 15) as usize;
+    let mut snum = [0u8; 4];
+    snum.copy_from_slice(&sign[offset..offset + 4]);
+    let snum = u32::from_be_bytes(snum) & 0x7fff_ffff;
+    (snum as u64 % 10u64.pow(digits as u32)) as u32
+}
+
+fn safe_format(mut snum: u32, dest: &mut [u8]) {
+    for digit in dest.iter_mut().rev() {
+        *digit = b'0' + (snum % 10) as u8;
+        snum /= 10;
+    }
+}
+
+fn unsafe_truncate(sign: &[u8], digits: u8) -> u32 {
+    let offset = (sign[sign.len() - 1] & 15) as usize;
+    let snum = unsafe {
+        let mut snum = mem::MaybeUninit::<u32>::uninit();
+        ptr::copy_nonoverlapping(sign.as_ptr().add(offset), snum.as_mut_ptr() as _, 4);
+        snum.assume_init().to_be() & 0x7fff_ffff
+    };
+    (snum as u64 % 10u64.pow(digits as u32)) as u32
+}
+
+fn unsafe_format(snum: u32, dest: &mut [u8]) {
+    struct WriteBuffer(*mut u8, usize);
+    impl Write for WriteBuffer {
+        #[inline]
+        fn write_str(&mut self, text: &str) -> fmt::Result {
+            let written_len = text.len();
+            unsafe {
+                ptr::copy_nonoverlapping(text.as_ptr(), self.0, written_len);
+                self.0 = self.0.add(written_len);
+            }
+            self.1 -= written_len;
+            Ok(())
+        }
+    }
+
+    let len = dest.len();
+    let mut buffer = WriteBuffer(dest.as_mut_ptr(), len);
+    let _ = write!(buffer, "{:0width$}", snum, width = len);
+}
+
+fn main() {
+    let hotp = HOTP::new(Algorithm::SHA1, SECRET);
+    let tag: Vec<u8> = hotp.sign(1).as_ref().to_vec();
+    let mut dest = [0u8; 6];
+
+    assert_eq!(safe_truncate(&tag, 6), unsafe_truncate(&tag, 6));
+    let (mut safe, mut previous) = ([0u8; 6], [0u8; 6]);
+    safe_format(82772, &mut safe);
+    unsafe_format(82772, &mut previous);
+    assert_eq!(safe, previous);
+
+    let elapsed = measure(|_| {
+        safe_format(safe_truncate(black_box(&tag), 6), &mut dest);
+        black_box(&dest);
+    });
+    println!("truncate + format (safe):     {:>8?}", elapsed);
+    let elapsed = measure(|_| {
+        unsafe_format(unsafe_truncate(black_box(&tag), 6), &mut dest);
+        black_box(&dest);
+    });
+    println!("truncate + format (previous): {:>8?}", elapsed);
+
+    let elapsed = measure(|counter| {
+        hotp.generate_to(counter, &mut dest);
+        black_box(&dest);
+    });
+    println!("generate_to (safe):           {:>8?}", elapsed);
+    let elapsed = measure(|counter| {
+        let sign = hotp.sign(counter);
+        unsafe_format(unsafe_truncate(sign.as_ref(), 6), &mut dest);
+        black_box(&dest);
+    });
+    println!("generate_to (previous):       {:>8?}", elapsed);
+}
@@ -0,0 +1,38 @@
+//!Measures cost of verification depending on skew.
+//!
+//!Token that is never accepted is used, so every window within skew is checked.
+//!Note that each window requires full HMAC computation, as `lhash` does not allow to retain
+//!intermediate digest state of the key.
+//!
+//!Run with `cargo bench`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use otpshka::{Algorithm, TOTP};
+
+const SECRET: [u8; 10] = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+const TIME: u64 = 1606206950;
+const ITERATIONS: u32 = 20_000;
+
+fn measure<F: FnMut() -> bool>(mut cb: F) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(cb());
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn main() {
+    let token = "000000";
+
+    for algorithm in [Algorithm::SHA1, Algorithm::SHA256, Algorithm::SHA512].iter() {
+        for skew in [1u8, 30, 90, 255].iter() {
+            let mut totp = TOTP::new(*algorithm, SECRET);
+            totp.skew = *skew;
+
+            let elapsed = measure(|| totp.verify(black_box(token), black_box(TIME)));
+            println!("{:>6} skew={:>3}s (max {:>2} HMACs): {:>9?}/verify", algorithm.name(), skew, totp.verify_cost(), elapsed);
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use core::fmt::Write;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{Algorithm, TOTP, MAX_DIGITS};
+use crate::uri::{self, ParseUriError};
+
+///`TOTP` account, as used by authenticator applications.
+///
+///Holds everything that is necessary to provision it via `otpauth` URI.
+pub struct Account {
+    ///Issuer of account (e.g. name of service).
+    pub issuer: Option<String>,
+    ///Name of account (e.g. user name or email).
+    pub name: String,
+    ///Raw secret
+    ///
+    ///With `zeroize` feature it is overwritten with zeros on drop.
+    pub secret: Vec<u8>,
+    ///HMAC algorithm.
+    ///
+    ///Default is SHA-1.
+    pub algorithm: Algorithm,
+    ///Number of digits in password.
+    ///
+    ///Default is 6.
+    pub digits: u8,
+    ///Time window in seconds.
+    ///
+    ///Default is 30.
+    pub period: u64,
+}
+
+impl core::fmt::Debug for Account {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        fmt.debug_struct("Account")
+           .field("issuer", &self.issuer)
+           .field("name", &self.name)
+           .field("secret", &format_args!("<redacted>"))
+           .field("algorithm", &self.algorithm)
+           .field("digits", &self.digits)
+           .field("period", &self.period)
+           .finish()
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for Account {
+    fn drop(&mut self) {
+        crate::hotp::wipe_bytes(&mut self.secret);
+    }
+}
+
+impl Account {
+    #[inline]
+    ///Creates new account with default configuration.
+    pub fn new<T: Into<String>>(name: T, secret: Vec<u8>) -> Self {
+        Self {
+            issuer: None,
+            name: name.into(),
+            secret,
+            algorithm: Algorithm::SHA1,
+            digits: 6,
+            period: 30,
+        }
+    }
+
+    ///Creates `TOTP` for this account.
+    pub fn totp(&self) -> TOTP {
+        let mut totp = TOTP::new(self.algorithm, &self.secret);
+        totp.window = self.period;
+        totp.digits = self.digits;
+        totp
+    }
+
+    ///Creates `otpauth://totp/` URI, which can be used to provision account (e.g. via QR code).
+    ///
+    ///Secret is encoded as base32 without padding. All parameters are always present, making
+    ///output canonical: parsing it via `from_uri` and encoding again produces the same URI.
+    pub fn to_uri(&self) -> String {
+        let mut uri = uri::start_uri("totp", self.issuer.as_deref(), &self.name, &self.secret, self.algorithm, self.digits);
+        let _ = write!(uri, "&period={}", self.period);
+        uri
+    }
+
+    ///Parses `otpauth://totp/` URI.
+    ///
+    ///`secret` is required, while `algorithm`, `digits` and `period` fall back to defaults.
+    ///`issuer` parameter takes priority over issuer in label.
+    pub fn from_uri(uri: &str) -> Result<Self, ParseUriError> {
+        Self::parse_uri(uri, "totp").map(|(account, _)| account)
+    }
+
+    ///Parses `otpauth` URI of type `expected_kind`, returning account and its `counter` parameter.
+    ///
+    ///`counter` is only read for `hotp`.
+    pub(crate) fn parse_uri(uri: &str, expected_kind: &str) -> Result<(Self, Option<u64>), ParseUriError> {
+        let (kind, label, query) = uri::split_uri(uri)?;
+        if !kind.eq_ignore_ascii_case(expected_kind) {
+            return Err(ParseUriError::UnsupportedType);
+        }
+        let is_hotp = expected_kind == "hotp";
+
+        let (issuer, name) = uri::parse_label(label);
+        let mut account = Self::new(name, Vec::new());
+        account.issuer = issuer.map(|issuer| issuer.into_owned());
+
+        let mut has_secret = false;
+        let mut counter = None;
+        for (key, value) in uri::query_params(query) {
+            match key {
+                "secret" => {
+                    if has_secret {
+                        return Err(ParseUriError::DuplicateSecret);
+                    }
+                    has_secret = true;
+                    let secret = &mut account.secret;
+                    //Reserve whole decoded length up front, so that growing never leaves copies of secret behind.
+                    secret.reserve(value.len() * 5 / 8);
+                    if crate::base32::decode(value.as_bytes(), |byte| secret.push(byte)).is_err() {
+                        return Err(ParseUriError::InvalidBase32);
+                    }
+                },
+                "issuer" => account.issuer = Some(value.into_owned()),
+                "algorithm" => match Algorithm::from_name(&value) {
+                    Some(algorithm) => account.algorithm = algorithm,
+                    None => return Err(ParseUriError::UnsupportedAlgorithm),
+                },
+                "digits" => match value.parse() {
+                    Ok(digits) if digits > 0 && digits <= MAX_DIGITS => account.digits = digits,
+                    _ => return Err(ParseUriError::InvalidDigits),
+                },
+                "period" => match value.parse() {
+                    Ok(period) if period > 0 => account.period = period,
+                    _ => return Err(ParseUriError::InvalidPeriod),
+                },
+                "counter" if is_hotp => match value.parse() {
+                    Ok(value) => counter = Some(value),
+                    Err(_) => return Err(ParseUriError::InvalidCounter),
+                },
+                _ => continue,
+            }
+        }
+
+        if !has_secret || account.secret.is_empty() {
+            return Err(ParseUriError::MissingSecret);
+        }
+
+        Ok((account, counter))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const URI: &str = "otpauth://totp/Big%20Corp:alice%40example.com?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&issuer=Big%20Corp&algorithm=SHA256&digits=8&period=60";
+
+    #[test]
+    fn should_encode_uri() {
+        let mut account = Account::new("alice@example.com", b"12345678901234567890".to_vec());
+        assert_eq!(account.to_uri(), "otpauth://totp/alice%40example.com?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&algorithm=SHA1&digits=6&period=30");
+
+        account.issuer = Some("Big Corp".into());
+        account.algorithm = Algorithm::SHA256;
+        account.digits = 8;
+        account.period = 60;
+        assert_eq!(account.to_uri(), URI);
+    }
+
+    #[test]
+    fn should_round_trip_uri() {
+        let account = Account::from_uri(URI).expect("to parse");
+        assert_eq!(account.issuer.as_deref(), Some("Big Corp"));
+        assert_eq!(account.name, "alice@example.com");
+        assert_eq!(account.secret, b"12345678901234567890");
+        assert_eq!(account.algorithm, Algorithm::SHA256);
+        assert_eq!(account.digits, 8);
+        assert_eq!(account.period, 60);
+
+        let uri = account.to_uri();
+        assert_eq!(uri, URI);
+        assert_eq!(Account::from_uri(&uri).expect("to parse").to_uri(), uri);
+    }
+
+    #[test]
+    fn should_redact_debug() {
+        let account = Account::from_uri(URI).expect("to parse");
+        let output = alloc::format!("{:?}", account);
+        assert_eq!(output, "Account { issuer: Some(\"Big Corp\"), name: \"alice@example.com\", secret: <redacted>, algorithm: SHA256, digits: 8, period: 60 }");
+        assert!(!output.contains("GEZDGNBV"));
+    }
+
+    #[test]
+    fn should_parse_uri_with_defaults() {
+        let account = Account::from_uri("otpauth://totp/GitHub:%20alice?secret=gezdgnbvgy3tqojqgezdgnbvgy3tqojq").expect("to parse");
+        assert_eq!(account.issuer.as_deref(), Some("GitHub"));
+        assert_eq!(account.name, "alice");
+        assert_eq!(account.algorithm, Algorithm::SHA1);
+        assert_eq!(account.digits, 6);
+        assert_eq!(account.period, 30);
+
+        let totp = account.totp();
+        assert_eq!(totp.window, 30);
+        assert_eq!(totp.digits(), 6);
+        assert!(totp.verify("287082", 59));
+    }
+
+    #[test]
+    fn should_reject_invalid_uri() {
+        assert_eq!(Account::from_uri("https://totp/alice?secret=GEZDGNBV").err(), Some(ParseUriError::InvalidScheme));
+        assert_eq!(Account::from_uri("otpauth://hotp/alice?secret=GEZDGNBV").err(), Some(ParseUriError::UnsupportedType));
+        assert_eq!(Account::from_uri("otpauth://totp/alice").err(), Some(ParseUriError::MissingSecret));
+        assert_eq!(Account::from_uri("otpauth://totp/alice?secret=").err(), Some(ParseUriError::MissingSecret));
+        assert_eq!(Account::from_uri("otpauth://totp/alice?secret=GEZ1").err(), Some(ParseUriError::InvalidBase32));
+        assert_eq!(Account::from_uri("otpauth://totp/alice?secret=GEZDGNBV&secret=GEZDGNBV").err(), Some(ParseUriError::DuplicateSecret));
+        assert_eq!(Account::from_uri("otpauth://totp/alice?secret=&secret=GEZDGNBV").err(), Some(ParseUriError::DuplicateSecret));
+        assert_eq!(Account::from_uri("otpauth://totp/alice?secret=GEZDGNBV&algorithm=MD5").err(), Some(ParseUriError::UnsupportedAlgorithm));
+        assert_eq!(Account::from_uri("otpauth://totp/alice?secret=GEZDGNBV&digits=0").err(), Some(ParseUriError::InvalidDigits));
+        assert_eq!(Account::from_uri("otpauth://totp/alice?secret=GEZDGNBV&digits=11").err(), Some(ParseUriError::InvalidDigits));
+        assert_eq!(Account::from_uri("otpauth://totp/alice?secret=GEZDGNBV&period=0").err(), Some(ParseUriError::InvalidPeriod));
+        assert_eq!(Account::from_uri("otpauth://totp/alice?secret=GEZDGNBV&period=x").err(), Some(ParseUriError::InvalidPeriod));
+    }
+}
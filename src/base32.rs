@@ -0,0 +1,154 @@
+use core::fmt;
+
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///Errors of decoding base32 secret.
+pub enum Base32Error {
+    ///Input contains character outside of base32 alphabet or data after padding.
+    InvalidCharacter,
+    ///Input contains no data.
+    Empty,
+}
+
+impl fmt::Display for Base32Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Base32Error::InvalidCharacter => fmt.write_str("Invalid base32 character"),
+            Base32Error::Empty => fmt.write_str("Base32 input is empty"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Base32Error {}
+
+#[cfg(feature = "alloc")]
+///RFC 4648 base32 alphabet
+const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+#[inline]
+fn decode_char(ch: u8) -> Option<u8> {
+    match ch {
+        b'A'..=b'Z' => Some(ch - b'A'),
+        b'a'..=b'z' => Some(ch - b'a'),
+        b'2'..=b'7' => Some(ch - b'2' + 26),
+        _ => None,
+    }
+}
+
+#[cfg(feature = "alloc")]
+///Encodes `input` as RFC 4648 base32 without padding.
+pub(crate) fn encode(input: &[u8]) -> String {
+    let mut result = String::with_capacity(input.len() * 8 / 5 + 1);
+
+    let mut buffer = 0u16;
+    let mut bits = 0;
+    for byte in input {
+        buffer = (buffer << 8) | *byte as u16;
+        bits += 8;
+
+        while bits >= 5 {
+            bits -= 5;
+            result.push(ALPHABET[(buffer >> bits) as usize & 0x1f] as char);
+        }
+    }
+
+    if bits > 0 {
+        result.push(ALPHABET[(buffer << (5 - bits)) as usize & 0x1f] as char);
+    }
+
+    result
+}
+
+///Decodes RFC 4648 base32 `input`, passing each decoded byte to `out`.
+///
+///Decoding is case insensitive and ignores ASCII whitespace, while padding is optional.
+///
+///Returns error on invalid character, while empty input is accepted.
+pub(crate) fn decode<F: FnMut(u8)>(input: &[u8], mut out: F) -> Result<(), Base32Error> {
+    let mut buffer = 0u16;
+    let mut bits = 0;
+    let mut padding = false;
+
+    for ch in input {
+        if ch.is_ascii_whitespace() {
+            continue;
+        } else if *ch == b'=' {
+            padding = true;
+            continue;
+        } else if padding {
+            return Err(Base32Error::InvalidCharacter);
+        }
+
+        let value = match decode_char(*ch) {
+            Some(value) => value,
+            None => return Err(Base32Error::InvalidCharacter),
+        };
+        buffer = (buffer << 5) | value as u16;
+        bits += 5;
+
+        if bits >= 8 {
+            bits -= 8;
+            out((buffer >> bits) as u8);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode_to<'a>(input: &str, buffer: &'a mut [u8; 64]) -> Option<&'a [u8]> {
+        let mut len = 0;
+        decode(input.as_bytes(), |byte| {
+            buffer[len] = byte;
+            len += 1;
+        }).ok()?;
+        Some(&buffer[..len])
+    }
+
+    #[test]
+    fn should_decode_rfc4648() {
+        let mut buffer = [0u8; 64];
+        assert_eq!(decode_to("", &mut buffer), Some(&b""[..]));
+        assert_eq!(decode_to("MY======", &mut buffer), Some(&b"f"[..]));
+        assert_eq!(decode_to("MZXQ====", &mut buffer), Some(&b"fo"[..]));
+        assert_eq!(decode_to("MZXW6===", &mut buffer), Some(&b"foo"[..]));
+        assert_eq!(decode_to("MZXW6YQ=", &mut buffer), Some(&b"foob"[..]));
+        assert_eq!(decode_to("MZXW6YTB", &mut buffer), Some(&b"fooba"[..]));
+        assert_eq!(decode_to("MZXW6YTBOI======", &mut buffer), Some(&b"foobar"[..]));
+    }
+
+    #[test]
+    fn should_decode_lenient_input() {
+        let mut buffer = [0u8; 64];
+        assert_eq!(decode_to("mzxw6ytboi", &mut buffer), Some(&b"foobar"[..]));
+        assert_eq!(decode_to("MZXW 6YTB\nOI", &mut buffer), Some(&b"foobar"[..]));
+    }
+
+    #[test]
+    fn should_reject_invalid_input() {
+        let mut buffer = [0u8; 64];
+        assert_eq!(decode_to("MZXW6YT1", &mut buffer), None);
+        assert_eq!(decode_to("MZXW6YT8", &mut buffer), None);
+        assert_eq!(decode_to("MZ=XW6YTB", &mut buffer), None);
+        assert_eq!(decode_to("MZXW-6YTB", &mut buffer), None);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn should_encode_rfc4648() {
+        assert_eq!(encode(b""), "");
+        assert_eq!(encode(b"f"), "MY");
+        assert_eq!(encode(b"fo"), "MZXQ");
+        assert_eq!(encode(b"foo"), "MZXW6");
+        assert_eq!(encode(b"foob"), "MZXW6YQ");
+        assert_eq!(encode(b"fooba"), "MZXW6YTB");
+        assert_eq!(encode(b"foobar"), "MZXW6YTBOI");
+        assert_eq!(encode(b"12345678901234567890"), "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ");
+    }
+}
@@ -0,0 +1,345 @@
+use core::fmt;
+use core::time::Duration;
+
+use crate::{Algorithm, TOTP, MAX_DIGITS};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+///Errors that can be produced by `TotpBuilder::build`
+///
+///Lists every problem of configuration at once.
+pub struct BuildError {
+    ///Secret is empty
+    pub empty_secret: bool,
+    ///Secret is shorter than output of algorithm.
+    ///
+    ///Only checked when `TotpBuilder::strict_secret` is enabled.
+    pub short_secret: bool,
+    ///Time window is zero
+    pub zero_window: bool,
+    ///Time window is not whole number of seconds.
+    ///
+    ///Only possible when window is set via `TotpBuilder::with_period`.
+    pub fractional_window: bool,
+    ///Skew is not whole number of seconds.
+    ///
+    ///Only possible when skew is set via `TotpBuilder::with_skew`.
+    pub fractional_skew: bool,
+    ///Skew exceeds `u8::MAX` seconds.
+    ///
+    ///Only possible when skew is set via `TotpBuilder::with_skew`.
+    pub skew_overflow: bool,
+    ///Number of digits is outside of `1..=MAX_DIGITS`
+    pub invalid_digits: Option<u8>,
+}
+
+impl BuildError {
+    #[inline]
+    ///Returns whether there is no problem.
+    pub const fn is_empty(&self) -> bool {
+        !self.empty_secret && !self.short_secret && !self.zero_window && !self.fractional_window
+            && !self.fractional_skew && !self.skew_overflow && self.invalid_digits.is_none()
+    }
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut separator = "";
+
+        if self.empty_secret {
+            fmt.write_str("Secret is empty")?;
+            separator = "; ";
+        }
+        if self.short_secret {
+            write!(fmt, "{}Secret is shorter than output of algorithm", separator)?;
+            separator = "; ";
+        }
+        if self.zero_window {
+            write!(fmt, "{}Time window is zero", separator)?;
+            separator = "; ";
+        }
+        if self.fractional_window {
+            write!(fmt, "{}Time window is not whole number of seconds", separator)?;
+            separator = "; ";
+        }
+        if self.fractional_skew {
+            write!(fmt, "{}Skew is not whole number of seconds", separator)?;
+            separator = "; ";
+        }
+        if self.skew_overflow {
+            write!(fmt, "{}Skew exceeds {} seconds", separator, u8::MAX)?;
+            separator = "; ";
+        }
+        if let Some(digits) = self.invalid_digits {
+            write!(fmt, "{}Number of digits {} is outside of 1..={}", separator, digits, MAX_DIGITS)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BuildError {}
+
+///Builder of `TOTP` with custom configuration.
+///
+///Configuration is validated on `build`.
+pub struct TotpBuilder<T> {
+    algorithm: Algorithm,
+    secret: T,
+    strict_secret: bool,
+    skew: u8,
+    fractional_skew: bool,
+    skew_overflow: bool,
+    window: u64,
+    fractional_window: bool,
+    t0: u64,
+    digits: u8,
+}
+
+impl<T: AsRef<[u8]>> TotpBuilder<T> {
+    #[inline]
+    ///Creates new builder with default configuration.
+    ///
+    ///- `algorithm` - Generally acceptable are HMAC based on `sha-1`, `sha-256` and `sha-512`
+    ///- `secret` - Raw bytes used to derive HMAC key. User is responsible to decode it before
+    ///  passing.
+    pub fn new(algorithm: Algorithm, secret: T) -> Self {
+        Self {
+            algorithm,
+            secret,
+            strict_secret: false,
+            skew: 1,
+            fractional_skew: false,
+            skew_overflow: false,
+            window: 30,
+            fractional_window: false,
+            t0: 0,
+            digits: 6,
+        }
+    }
+
+    #[inline(always)]
+    ///Sets whether to reject secrets shorter than output of algorithm.
+    ///
+    ///Default is `false`.
+    pub fn strict_secret(mut self, strict_secret: bool) -> Self {
+        self.strict_secret = strict_secret;
+        self
+    }
+
+    #[inline(always)]
+    ///Sets number of seconds allowed as network delay.
+    ///
+    ///Default is 1.
+    pub fn skew(mut self, skew: u8) -> Self {
+        self.skew = skew;
+        self.fractional_skew = false;
+        self.skew_overflow = false;
+        self
+    }
+
+    #[inline(always)]
+    ///Sets time window in seconds.
+    ///
+    ///Must not be zero. Default is 30.
+    pub fn window(mut self, window: u64) -> Self {
+        self.window = window;
+        self.fractional_window = false;
+        self
+    }
+
+    #[inline(always)]
+    ///Sets time window as `Duration`.
+    ///
+    ///Must be whole number of seconds and not zero. Default is 30 seconds.
+    pub fn with_period(mut self, period: Duration) -> Self {
+        self.window = period.as_secs();
+        self.fractional_window = period.subsec_nanos() != 0;
+        self
+    }
+
+    #[inline(always)]
+    ///Sets allowed network delay as `Duration`.
+    ///
+    ///Must be whole number of seconds and not exceed `u8::MAX` seconds. Default is 1 second.
+    pub fn with_skew(mut self, skew: Duration) -> Self {
+        self.skew = core::cmp::min(skew.as_secs(), u8::MAX as u64) as u8;
+        self.fractional_skew = skew.subsec_nanos() != 0;
+        self.skew_overflow = skew.as_secs() > u8::MAX as u64;
+        self
+    }
+
+    #[inline(always)]
+    ///Sets unix timestamp from which windows are counted.
+    ///
+    ///Default is 0.
+    pub fn t0(mut self, t0: u64) -> Self {
+        self.t0 = t0;
+        self
+    }
+
+    #[inline(always)]
+    ///Sets number of digits in generated password.
+    ///
+    ///Must be within `1..=MAX_DIGITS`. Default is 6.
+    pub fn digits(mut self, digits: u8) -> Self {
+        self.digits = digits;
+        self
+    }
+
+    ///Validates configuration and creates `TOTP`
+    pub fn build(self) -> Result<TOTP, BuildError> {
+        let secret_len = self.secret.as_ref().len();
+        let error = BuildError {
+            empty_secret: secret_len == 0,
+            short_secret: self.strict_secret && secret_len != 0 && secret_len < self.algorithm.digest_len(),
+            zero_window: self.window == 0,
+            fractional_window: self.fractional_window,
+            fractional_skew: self.fractional_skew,
+            skew_overflow: self.skew_overflow,
+            invalid_digits: match self.digits {
+                0 => Some(0),
+                digits if digits > MAX_DIGITS => Some(digits),
+                _ => None,
+            },
+        };
+
+        if !error.is_empty() {
+            return Err(error);
+        }
+
+        let mut totp = TOTP::new(self.algorithm, self.secret);
+        totp.skew = self.skew;
+        totp.window = self.window;
+        totp.t0 = self.t0;
+        totp.digits = self.digits;
+        Ok(totp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: [u8; 10] = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+
+    #[test]
+    fn should_build_totp_with_custom_config() {
+        let totp = TOTP::builder(Algorithm::SHA256, SECRET).skew(2).window(60).t0(15).digits(8).build().expect("to build");
+        assert_eq!(totp.skew, 2);
+        assert_eq!(totp.window, 60);
+        assert_eq!(totp.t0, 15);
+        assert_eq!(totp.digits(), 8);
+    }
+
+    #[test]
+    fn should_validate_digits() {
+        let invalid_digits = |digits| BuildError {
+            invalid_digits: Some(digits),
+            ..Default::default()
+        };
+
+        assert_eq!(TOTP::builder(Algorithm::SHA1, SECRET).digits(0).build().err(), Some(invalid_digits(0)));
+        assert_eq!(TOTP::builder(Algorithm::SHA1, SECRET).digits(MAX_DIGITS).build().expect("to build").digits(), MAX_DIGITS);
+        assert_eq!(TOTP::builder(Algorithm::SHA1, SECRET).digits(MAX_DIGITS + 1).build().err(), Some(invalid_digits(MAX_DIGITS + 1)));
+        assert_eq!(TOTP::builder(Algorithm::SHA1, SECRET).digits(12).build().err(), Some(invalid_digits(12)));
+
+        let totp = TOTP::builder(Algorithm::SHA1, SECRET).digits(10).build().expect("to build");
+        assert_eq!(totp.digits(), 10);
+        let mut token = [0u8; 10];
+        totp.generate_to(1606206826, &mut token[..]);
+        assert!(totp.verify(core::str::from_utf8(&token).expect("UTF-8 compatible output"), 1606206826));
+        assert_eq!(TOTP::builder(Algorithm::SHA1, SECRET).digits(11).build().err(), Some(invalid_digits(11)));
+    }
+
+    #[test]
+    fn should_match_defaults_of_new() {
+        let built = TOTP::builder(Algorithm::SHA1, SECRET).build().expect("to build");
+        let new = TOTP::new(Algorithm::SHA1, SECRET);
+        assert_eq!(built.skew, new.skew);
+        assert_eq!(built.window, new.window);
+        assert_eq!(built.t0, new.t0);
+        assert_eq!(built.digits(), new.digits());
+    }
+
+    #[test]
+    fn should_validate_secret() {
+        let error = TOTP::builder(Algorithm::SHA1, b"").build().expect_err("to fail");
+        assert_eq!(error, BuildError { empty_secret: true, ..Default::default() });
+
+        let error = TOTP::builder(Algorithm::SHA1, b"").strict_secret(true).build().expect_err("to fail");
+        assert_eq!(error, BuildError { empty_secret: true, ..Default::default() });
+
+        assert!(TOTP::builder(Algorithm::SHA256, SECRET).build().is_ok());
+        let error = TOTP::builder(Algorithm::SHA256, SECRET).strict_secret(true).build().expect_err("to fail");
+        assert_eq!(error, BuildError { short_secret: true, ..Default::default() });
+
+        assert!(TOTP::builder(Algorithm::SHA1, b"12345678901234567890").strict_secret(true).build().is_ok());
+    }
+
+    #[test]
+    fn should_validate_window() {
+        let error = TOTP::builder(Algorithm::SHA1, SECRET).window(0).build().expect_err("to fail");
+        assert_eq!(error, BuildError { zero_window: true, ..Default::default() });
+    }
+
+    #[test]
+    fn should_build_totp_with_durations() {
+        let totp = TOTP::builder(Algorithm::SHA1, SECRET).with_period(Duration::from_secs(60)).with_skew(Duration::from_secs(2)).build().expect("to build");
+        assert_eq!(totp.window, 60);
+        assert_eq!(totp.skew, 2);
+
+        let totp = TOTP::builder(Algorithm::SHA1, SECRET).with_skew(Duration::from_secs(u8::MAX as u64)).build().expect("to build");
+        assert_eq!(totp.skew, u8::MAX);
+
+        let error = TOTP::builder(Algorithm::SHA1, SECRET).with_skew(Duration::from_millis(2500)).build().expect_err("to fail");
+        assert_eq!(error, BuildError { fractional_skew: true, ..Default::default() });
+
+        let error = TOTP::builder(Algorithm::SHA1, SECRET).with_skew(Duration::from_secs(1000)).build().expect_err("to fail");
+        assert_eq!(error, BuildError { skew_overflow: true, ..Default::default() });
+
+        let error = TOTP::builder(Algorithm::SHA1, SECRET).with_skew(Duration::from_millis(256_500)).build().expect_err("to fail");
+        assert_eq!(error, BuildError { fractional_skew: true, skew_overflow: true, ..Default::default() });
+
+        let totp = TOTP::builder(Algorithm::SHA1, SECRET).with_skew(Duration::from_secs(1000)).skew(3).build().expect("to build");
+        assert_eq!(totp.skew, 3);
+
+        let error = TOTP::builder(Algorithm::SHA1, SECRET).with_period(Duration::from_millis(500)).build().expect_err("to fail");
+        assert_eq!(error, BuildError { zero_window: true, fractional_window: true, ..Default::default() });
+
+        let error = TOTP::builder(Algorithm::SHA1, SECRET).with_period(Duration::from_millis(30500)).build().expect_err("to fail");
+        assert_eq!(error, BuildError { fractional_window: true, ..Default::default() });
+
+        let error = TOTP::builder(Algorithm::SHA1, SECRET).with_period(Duration::from_secs(0)).build().expect_err("to fail");
+        assert_eq!(error, BuildError { zero_window: true, ..Default::default() });
+
+        let totp = TOTP::builder(Algorithm::SHA1, SECRET).with_period(Duration::from_millis(500)).window(30).build().expect("to build");
+        assert_eq!(totp.window, 30);
+    }
+
+    #[test]
+    fn should_report_every_problem() {
+        let error = TOTP::builder(Algorithm::SHA1, b"").window(0).digits(0).build().expect_err("to fail");
+        assert_eq!(error, BuildError {
+            empty_secret: true,
+            short_secret: false,
+            zero_window: true,
+            fractional_window: false,
+            fractional_skew: false,
+            skew_overflow: false,
+            invalid_digits: Some(0),
+        });
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn should_display_every_problem() {
+        use std::string::ToString;
+
+        let error = TOTP::builder(Algorithm::SHA1, b"").window(0).digits(11).build().expect_err("to fail");
+        assert_eq!(error.to_string(), "Secret is empty; Time window is zero; Number of digits 11 is outside of 1..=10");
+
+        let error = TOTP::builder(Algorithm::SHA1, SECRET).with_skew(Duration::from_millis(256_500)).build().expect_err("to fail");
+        assert_eq!(error.to_string(), "Skew is not whole number of seconds; Skew exceeds 255 seconds");
+    }
+}
@@ -0,0 +1,378 @@
+use core::fmt;
+use alloc::string::String;
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::de::{self, Visitor};
+
+use crate::{Algorithm, HOTP, TOTP, HotpCounter, base32};
+
+impl Serialize for Algorithm {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for Algorithm {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct AlgorithmVisitor;
+
+        impl<'de> Visitor<'de> for AlgorithmVisitor {
+            type Value = Algorithm;
+
+            fn expecting(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt.write_str("one of SHA1, SHA256 or SHA512")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                match Algorithm::from_name(value) {
+                    Some(algorithm) => Ok(algorithm),
+                    None => Err(E::invalid_value(de::Unexpected::Str(value), &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_str(AlgorithmVisitor)
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+///Serializable configuration of `TOTP`.
+///
+///Secret is stored as base32 without padding, same as in `otpauth` URI.
+///`TOTP` itself is deserialized via this struct and, with `retain-secret` feature, serialized too.
+pub struct TotpConfig {
+    ///HMAC algorithm.
+    pub algorithm: Algorithm,
+    ///Base32 encoded secret.
+    pub secret: String,
+    ///Number of digits in password.
+    pub digits: u8,
+    ///Time window in seconds.
+    pub window: u64,
+    ///Number of seconds allowed as network delay.
+    pub skew: u8,
+    ///Unix timestamp from which windows are counted.
+    ///
+    ///Optional, default is 0.
+    #[serde(default)]
+    pub t0: u64,
+}
+
+impl fmt::Debug for TotpConfig {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("TotpConfig")
+           .field("algorithm", &self.algorithm)
+           .field("secret", &format_args!("<redacted>"))
+           .field("digits", &self.digits)
+           .field("window", &self.window)
+           .field("skew", &self.skew)
+           .field("t0", &self.t0)
+           .finish()
+    }
+}
+
+impl TotpConfig {
+    ///Creates `TOTP`, validating configuration same as `TotpBuilder::build`.
+    pub fn build(&self) -> Result<TOTP, ConfigError> {
+        let secret = decode_secret(&self.secret)?;
+        TOTP::builder(self.algorithm, &secret.0).skew(self.skew)
+                                                .window(self.window)
+                                                .t0(self.t0)
+                                                .digits(self.digits)
+                                                .build()
+                                                .map_err(ConfigError::Build)
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+///Serializable configuration of `HotpCounter`.
+///
+///Secret is stored as base32 without padding, same as in `otpauth` URI.
+///`HotpCounter` itself is deserialized via this struct and, with `retain-secret` feature, serialized too.
+pub struct HotpConfig {
+    ///HMAC algorithm.
+    pub algorithm: Algorithm,
+    ///Base32 encoded secret.
+    pub secret: String,
+    ///Current counter.
+    pub counter: u64,
+    ///Number of counters after current one that are also accepted by `HotpCounter::verify`.
+    ///
+    ///Optional, default is 0.
+    #[serde(default)]
+    pub look_ahead: u64,
+}
+
+impl fmt::Debug for HotpConfig {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("HotpConfig")
+           .field("algorithm", &self.algorithm)
+           .field("secret", &format_args!("<redacted>"))
+           .field("counter", &self.counter)
+           .field("look_ahead", &self.look_ahead)
+           .finish()
+    }
+}
+
+impl HotpConfig {
+    ///Creates `HotpCounter`, rejecting empty or invalid secret.
+    pub fn build(&self) -> Result<HotpCounter, ConfigError> {
+        let secret = decode_secret(&self.secret)?;
+        let mut result = HotpCounter::new(HOTP::new(self.algorithm, &secret.0), self.counter);
+        result.look_ahead = self.look_ahead;
+        Ok(result)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///Errors of building from `TotpConfig` or `HotpConfig`
+pub enum ConfigError {
+    ///Secret is not valid base32
+    InvalidSecret,
+    ///Secret is empty
+    EmptySecret,
+    ///Configuration is invalid
+    Build(crate::BuildError),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::InvalidSecret => fmt.write_str("Secret is not valid base32"),
+            ConfigError::EmptySecret => fmt.write_str("Secret is empty"),
+            ConfigError::Build(error) => fmt::Display::fmt(error, fmt),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ConfigError {}
+
+///Decoded secret, which is wiped on drop with `zeroize` feature.
+struct DecodedSecret(alloc::vec::Vec<u8>);
+
+#[cfg(feature = "zeroize")]
+impl Drop for DecodedSecret {
+    fn drop(&mut self) {
+        crate::hotp::wipe_bytes(&mut self.0);
+    }
+}
+
+fn decode_secret(encoded: &str) -> Result<DecodedSecret, ConfigError> {
+    //Whole decoded length is allocated up front, so that growing never leaves copies of secret behind.
+    let mut secret = alloc::vec::Vec::with_capacity(encoded.len() * 5 / 8);
+    if base32::decode(encoded.as_bytes(), |byte| secret.push(byte)).is_err() {
+        return Err(ConfigError::InvalidSecret);
+    }
+
+    match secret.is_empty() {
+        true => Err(ConfigError::EmptySecret),
+        false => Ok(DecodedSecret(secret)),
+    }
+}
+
+#[cfg(feature = "retain-secret")]
+impl<K: core::borrow::Borrow<HOTP>> TOTP<K> {
+    ///Returns serializable configuration of this instance.
+    ///
+    ///Requires `retain-secret` feature.
+    pub fn to_config(&self) -> TotpConfig {
+        TotpConfig {
+            algorithm: self.algorithm(),
+            secret: base32::encode(self.hotp().secret()),
+            digits: self.digits(),
+            window: self.window,
+            skew: self.skew,
+            t0: self.t0,
+        }
+    }
+}
+
+#[cfg(feature = "retain-secret")]
+impl<K: core::borrow::Borrow<HOTP>> HotpCounter<K> {
+    ///Returns serializable configuration of this instance.
+    ///
+    ///Requires `retain-secret` feature.
+    pub fn to_config(&self) -> HotpConfig {
+        HotpConfig {
+            algorithm: self.hotp().algorithm(),
+            secret: base32::encode(self.hotp().secret()),
+            counter: self.counter(),
+            look_ahead: self.look_ahead,
+        }
+    }
+}
+
+#[cfg(feature = "retain-secret")]
+impl<K: core::borrow::Borrow<HOTP>> Serialize for TOTP<K> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_config().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for TOTP {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        TotpConfig::deserialize(deserializer)?.build().map_err(de::Error::custom)
+    }
+}
+
+#[cfg(feature = "retain-secret")]
+impl<K: core::borrow::Borrow<HOTP>> Serialize for HotpCounter<K> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_config().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for HotpCounter {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        HotpConfig::deserialize(deserializer)?.build().map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: [u8; 10] = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+
+    #[test]
+    fn should_serialize_algorithm_as_name() {
+        for algorithm in [Algorithm::SHA1, Algorithm::SHA256, Algorithm::SHA512].iter() {
+            let json = serde_json::to_string(algorithm).expect("to serialize");
+            assert_eq!(json, alloc::format!("\"{}\"", algorithm.name()));
+            assert_eq!(serde_json::from_str::<Algorithm>(&json).expect("to deserialize"), *algorithm);
+        }
+
+        assert_eq!(serde_json::from_str::<Algorithm>("\"sha256\"").expect("to deserialize"), Algorithm::SHA256);
+        assert!(serde_json::from_str::<Algorithm>("\"MD5\"").is_err());
+        assert!(serde_json::from_str::<Algorithm>("1").is_err());
+    }
+
+    #[test]
+    fn should_round_trip_totp_config() {
+        let config = TotpConfig {
+            algorithm: Algorithm::SHA256,
+            secret: "JBSWY3DPEHPK3PXP".into(),
+            digits: 8,
+            window: 60,
+            skew: 2,
+            t0: 15,
+        };
+        let totp = TOTP::builder(Algorithm::SHA256, SECRET).skew(2).window(60).t0(15).digits(8).build().expect("to build");
+
+        let json = serde_json::to_string(&config).expect("to serialize");
+        assert_eq!(json, r#"{"algorithm":"SHA256","secret":"JBSWY3DPEHPK3PXP","digits":8,"window":60,"skew":2,"t0":15}"#);
+        assert_eq!(serde_json::from_str::<TotpConfig>(&json).expect("to deserialize"), config);
+
+        let restored: TOTP = serde_json::from_str(&json).expect("to deserialize");
+        assert_eq!((restored.window, restored.skew, restored.t0, restored.digits()), (60, 2, 15, 8));
+        for time in [0u64, 59, 1606206826, 1606206950].iter() {
+            assert_eq!(restored.generate_num(*time, 8), totp.generate_num(*time, 8));
+        }
+
+        let restored: TOTP = serde_json::from_str(r#"{"algorithm":"SHA1","secret":"JBSWY3DPEHPK3PXP","digits":6,"window":30,"skew":1}"#).expect("to deserialize");
+        assert_eq!(restored.t0, 0);
+        assert!(restored.verify("082772", 1606206950));
+    }
+
+    #[cfg(feature = "retain-secret")]
+    #[test]
+    fn should_round_trip_totp() {
+        let totp = TOTP::builder(Algorithm::SHA256, SECRET).skew(2).window(60).t0(15).digits(8).build().expect("to build");
+
+        let json = serde_json::to_string(&totp).expect("to serialize");
+        assert_eq!(json, r#"{"algorithm":"SHA256","secret":"JBSWY3DPEHPK3PXP","digits":8,"window":60,"skew":2,"t0":15}"#);
+
+        let restored: TOTP = serde_json::from_str(&json).expect("to deserialize");
+        assert_eq!(restored.to_config(), totp.to_config());
+        for time in [0u64, 59, 1606206826, 1606206950].iter() {
+            assert_eq!(restored.generate_num(*time, 8), totp.generate_num(*time, 8));
+        }
+    }
+
+    #[test]
+    fn should_reject_invalid_totp_config() {
+        assert!(serde_json::from_str::<TOTP>(r#"{"algorithm":"SHA1","secret":"","digits":6,"window":30,"skew":1}"#).is_err());
+        assert!(serde_json::from_str::<TOTP>(r#"{"algorithm":"SHA1","secret":"JBSW1","digits":6,"window":30,"skew":1}"#).is_err());
+        assert!(serde_json::from_str::<TOTP>(r#"{"algorithm":"SHA1","secret":"JBSWY3DPEHPK3PXP","digits":11,"window":30,"skew":1}"#).is_err());
+        assert!(serde_json::from_str::<TOTP>(r#"{"algorithm":"SHA1","secret":"JBSWY3DPEHPK3PXP","digits":6,"window":0,"skew":1}"#).is_err());
+
+        let config = TotpConfig {
+            algorithm: Algorithm::SHA1,
+            secret: "JBSWY3DPEHPK3PXP".into(),
+            digits: 0,
+            window: 30,
+            skew: 1,
+            t0: 0,
+        };
+        assert_eq!(config.build().err(), Some(ConfigError::Build(crate::BuildError { invalid_digits: Some(0), ..Default::default() })));
+    }
+
+    #[test]
+    fn should_round_trip_hotp_config() {
+        let config = HotpConfig {
+            algorithm: Algorithm::SHA1,
+            secret: "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ".into(),
+            counter: 5,
+            look_ahead: 3,
+        };
+
+        let json = serde_json::to_string(&config).expect("to serialize");
+        assert_eq!(json, r#"{"algorithm":"SHA1","secret":"GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ","counter":5,"look_ahead":3}"#);
+        assert_eq!(serde_json::from_str::<HotpConfig>(&json).expect("to deserialize"), config);
+
+        let mut restored: HotpCounter = serde_json::from_str(&json).expect("to deserialize");
+        assert_eq!(restored.counter(), 5);
+        assert_eq!(restored.look_ahead, 3);
+        assert_eq!(restored.next::<6>(), Some(*b"254676"));
+
+        let restored: HotpCounter = serde_json::from_str(r#"{"algorithm":"SHA1","secret":"GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ","counter":0}"#).expect("to deserialize");
+        assert_eq!(restored.look_ahead, 0);
+        assert_eq!(HotpConfig { secret: "".into(), ..config }.build().err(), Some(ConfigError::EmptySecret));
+    }
+
+    #[cfg(feature = "retain-secret")]
+    #[test]
+    fn should_round_trip_hotp_counter() {
+        let mut hotp = HotpCounter::new(HOTP::new(Algorithm::SHA1, b"12345678901234567890"), 5);
+        hotp.look_ahead = 3;
+
+        let json = serde_json::to_string(&hotp).expect("to serialize");
+        assert_eq!(json, r#"{"algorithm":"SHA1","secret":"GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ","counter":5,"look_ahead":3}"#);
+
+        let mut restored: HotpCounter = serde_json::from_str(&json).expect("to deserialize");
+        assert_eq!(restored.counter(), 5);
+        assert_eq!(restored.look_ahead, 3);
+        assert_eq!(restored.next::<6>(), Some(*b"254676"));
+        assert_eq!(hotp.next::<6>(), Some(*b"254676"));
+        assert_eq!(restored.to_config(), hotp.to_config());
+    }
+
+    #[test]
+    fn should_redact_secret_in_config_debug() {
+        let totp = TotpConfig {
+            algorithm: Algorithm::SHA256,
+            secret: "JBSWY3DPEHPK3PXP".into(),
+            digits: 6,
+            window: 30,
+            skew: 1,
+            t0: 0,
+        };
+        let output = alloc::format!("{:?}", totp);
+        assert!(!output.contains("JBSWY3DPEHPK3PXP"));
+        assert_eq!(output, "TotpConfig { algorithm: SHA256, secret: <redacted>, digits: 6, window: 30, skew: 1, t0: 0 }");
+
+        let hotp = HotpConfig {
+            algorithm: Algorithm::SHA1,
+            secret: "JBSWY3DPEHPK3PXP".into(),
+            counter: 5,
+            look_ahead: 0,
+        };
+        let output = alloc::format!("{:?}", hotp);
+        assert!(!output.contains("JBSWY3DPEHPK3PXP"));
+        assert_eq!(output, "HotpConfig { algorithm: SHA1, secret: <redacted>, counter: 5, look_ahead: 0 }");
+    }
+}
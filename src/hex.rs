@@ -0,0 +1,85 @@
+use core::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///Errors of decoding hex secret.
+pub enum HexError {
+    ///Input contains character outside of `0-9`, `a-f` and `A-F`.
+    InvalidCharacter,
+    ///Input has odd number of characters.
+    OddLength,
+    ///Input contains no data.
+    Empty,
+}
+
+impl fmt::Display for HexError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HexError::InvalidCharacter => fmt.write_str("Invalid hex character"),
+            HexError::OddLength => fmt.write_str("Hex input has odd length"),
+            HexError::Empty => fmt.write_str("Hex input is empty"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for HexError {}
+
+#[inline]
+///Returns value of hex digit, ignoring case.
+pub(crate) fn hex_value(byte: u8) -> Option<u8> {
+    match byte {
+        b'0'..=b'9' => Some(byte - b'0'),
+        b'a'..=b'f' => Some(byte - b'a' + 10),
+        b'A'..=b'F' => Some(byte - b'A' + 10),
+        _ => None,
+    }
+}
+
+///Decodes hex `input`, passing each decoded byte to `out`.
+///
+///Decoding is case insensitive, while empty input is accepted.
+pub(crate) fn decode<F: FnMut(u8)>(input: &[u8], mut out: F) -> Result<(), HexError> {
+    if input.len() & 1 == 1 {
+        return Err(HexError::OddLength);
+    }
+
+    for pair in input.chunks_exact(2) {
+        match (hex_value(pair[0]), hex_value(pair[1])) {
+            (Some(high), Some(low)) => out(high << 4 | low),
+            _ => return Err(HexError::InvalidCharacter),
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode_to<'a>(input: &str, buffer: &'a mut [u8; 64]) -> Result<&'a [u8], HexError> {
+        let mut len = 0;
+        decode(input.as_bytes(), |byte| {
+            buffer[len] = byte;
+            len += 1;
+        })?;
+        Ok(&buffer[..len])
+    }
+
+    #[test]
+    fn should_decode_hex() {
+        let mut buffer = [0u8; 64];
+        assert_eq!(decode_to("", &mut buffer), Ok(&b""[..]));
+        assert_eq!(decode_to("48656c6C6f21DEADbeef", &mut buffer), Ok(&[72, 101, 108, 108, 111, 33, 222, 173, 190, 239][..]));
+        assert_eq!(decode_to("3132333435363738393031323334353637383930", &mut buffer), Ok(&b"12345678901234567890"[..]));
+    }
+
+    #[test]
+    fn should_reject_invalid_hex() {
+        let mut buffer = [0u8; 64];
+        assert_eq!(decode_to("abc", &mut buffer), Err(HexError::OddLength));
+        assert_eq!(decode_to("0g", &mut buffer), Err(HexError::InvalidCharacter));
+        assert_eq!(decode_to("0x12", &mut buffer), Err(HexError::InvalidCharacter));
+        assert_eq!(decode_to("12 3", &mut buffer), Err(HexError::InvalidCharacter));
+    }
+}
@@ -1,6 +1,7 @@
-use core::{mem, ptr};
+use core::{fmt, mem};
 
-use crate::Algorithm;
+use crate::{Algorithm, Base32Error, HexError, MAX_DIGITS};
+use crate::token::parse_token;
 
 enum HmacKey {
     Sha1(lhash::HmacKey::<lhash::Sha1>),
@@ -26,10 +27,308 @@ impl AsRef<[u8]> for HmacOutput {
     }
 }
 
+///Copies HMAC `tag` to the start of zeroed array, returning it alongside length of `tag`.
+fn tag_to_array(tag: &[u8]) -> ([u8; 64], usize) {
+    let mut result = [0u8; 64];
+    result[..tag.len()].copy_from_slice(tag);
+    (result, tag.len())
+}
+
+#[cfg(feature = "prf")]
+///Domain separation prefix for `HOTP::prf` input.
+const PRF_DOMAIN: &[u8] = b"otpshka.prf:";
+
+#[cfg(feature = "prf")]
+fn prf_sign<D: lhash::Digest>(key: &lhash::HmacKey<D>, label: &[u8]) -> D::OutputType {
+    let mut input = [0u8; PRF_DOMAIN.len() + 64];
+    input[..PRF_DOMAIN.len()].copy_from_slice(PRF_DOMAIN);
+
+    let mut digest = D::new();
+    digest.update(label);
+    let label = digest.result();
+    let label = label.as_ref();
+
+    let input_len = PRF_DOMAIN.len() + label.len();
+    input[PRF_DOMAIN.len()..input_len].copy_from_slice(label);
+    key.sign(&input[..input_len])
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///Errors of invalid number of digits.
+pub enum DigitsError {
+    ///Number of digits is zero
+    Zero,
+    ///Number of digits exceeds `MAX_DIGITS`
+    TooMany,
+}
+
+impl fmt::Display for DigitsError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DigitsError::Zero => fmt.write_str("Number of digits is zero"),
+            DigitsError::TooMany => write!(fmt, "Number of digits exceeds {}", MAX_DIGITS),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DigitsError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///Errors of invalid secret.
+pub enum SecretError {
+    ///Secret is empty
+    Empty,
+}
+
+impl fmt::Display for SecretError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SecretError::Empty => fmt.write_str("Secret is empty"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SecretError {}
+
+///Converts HMAC tag into password as number with length of `digits`, using dynamic truncation.
+fn dynamic_truncate(sign: &[u8]) -> u32 {
+    //Offset is at most 15, so it is always within the shortest (`sha-1`) tag.
+    let offset = (sign[sign.len() - 1] & 15) as usize;
+    let mut snum = [0u8; 4];
+    snum.copy_from_slice(&sign[offset..offset + 4]);
+    u32::from_be_bytes(snum) & 0x7fff_ffff
+}
+
+fn truncate_num(snum: u32, digits: u8) -> u32 {
+    //Wide enough to hold `10^MAX_DIGITS`
+    const BASE: u64 = 10;
+
+    //31-bit value has at most `MAX_DIGITS` digits, so capping keeps full value without overflowing `pow`
+    let digits = core::cmp::min(digits, MAX_DIGITS);
+    (snum as u64 % BASE.pow(digits as u32)) as u32
+}
+
+///Alphabet of Steam Guard codes.
+const STEAM_ALPHABET: &[u8; 26] = b"23456789BCDFGHJKMNPQRTVWXY";
+
+///Checks whether `token` matches Steam Guard `code`, ignoring case.
+///
+///Comparison is performed over every character without early return.
+pub(crate) fn steam_matches(token: &[u8], code: &[u8; 5]) -> bool {
+    if token.len() != code.len() {
+        return false;
+    }
+
+    let mut diff = 0;
+    for (expected, actual) in code.iter().zip(token) {
+        diff |= expected ^ actual.to_ascii_uppercase();
+    }
+    diff == 0
+}
+
+///Maximum length of nonce accepted by `HOTP::sign_counter_with_nonce`
+pub const MAX_NONCE_LEN: usize = 64;
+
+///Domain separation prefix for `HOTP::sign_counter_with_nonce` input.
+const NONCE_DOMAIN: &[u8] = b"otpshka.nonce:";
+
+///Maximum block size among supported algorithms.
+const MAX_BLOCK_SIZE: usize = 128;
+
+///Accumulator of secret that is provided in chunks.
+///
+///Created via `HOTP::builder_secret`.
+///
+///It buffers up to `128` bytes of secret, while anything longer is hashed incrementally, same as
+///HMAC does for secrets exceeding block size of algorithm. Hashing starts only once secret exceeds buffer.
+pub struct SecretAccumulator {
+    buffer: [u8; MAX_BLOCK_SIZE],
+    len: usize,
+    ///Algorithm passed to `finish`, if known upfront. Otherwise secret is hashed with every algorithm.
+    algorithm: Option<Algorithm>,
+    sha1: lhash::Sha1,
+    sha256: lhash::Sha256,
+    sha512: lhash::Sha512,
+}
+
+impl SecretAccumulator {
+    #[inline]
+    ///Creates new empty accumulator.
+    pub const fn new() -> Self {
+        Self::for_algorithm(None)
+    }
+
+    #[inline]
+    ///Creates new empty accumulator, which is finished only with `algorithm`, if specified.
+    const fn for_algorithm(algorithm: Option<Algorithm>) -> Self {
+        Self {
+            buffer: [0; MAX_BLOCK_SIZE],
+            len: 0,
+            algorithm,
+            sha1: lhash::Sha1::new(),
+            sha256: lhash::Sha256::new(),
+            sha512: lhash::Sha512::new(),
+        }
+    }
+
+    ///Appends `chunk` to the secret.
+    pub fn update(&mut self, chunk: &[u8]) {
+        let new_len = self.len.saturating_add(chunk.len());
+        if new_len <= self.buffer.len() {
+            self.buffer[self.len..new_len].copy_from_slice(chunk);
+            self.len = new_len;
+            return;
+        }
+
+        //Secret no longer fits buffer, hence it has to be hashed from the start.
+        if self.len <= self.buffer.len() {
+            self.update_digests(self.len, &[]);
+        }
+        self.update_digests(0, chunk);
+        self.len = new_len;
+    }
+
+    ///Hashes first `buffered` bytes of buffer followed by `chunk`, using only digests required by `finish`.
+    fn update_digests(&mut self, buffered: usize, chunk: &[u8]) {
+        let buffered = &self.buffer[..buffered];
+
+        //`sha-256` is always required for fingerprint
+        self.sha256.update(buffered);
+        self.sha256.update(chunk);
+        if let None | Some(Algorithm::SHA1) = self.algorithm {
+            self.sha1.update(buffered);
+            self.sha1.update(chunk);
+        }
+        if let None | Some(Algorithm::SHA512) = self.algorithm {
+            self.sha512.update(buffered);
+            self.sha512.update(chunk);
+        }
+    }
+
+    ///Initializes `HOTP` using accumulated secret and provided `algorithm`.
+    ///
+    ///When secret exceeds block size of `algorithm`, it is replaced with its hash, which produces
+    ///the same HMAC. Hence URIs created by `HOTP::to_uri` contain the hash instead of original secret.
+    pub fn finish(mut self, algorithm: Algorithm) -> HOTP {
+        //`HOTP::new` hashes secret exceeding block size itself
+        if self.len <= self.buffer.len() {
+            return HOTP::new(algorithm, &self.buffer[..self.len]);
+        }
+        debug_assert!(self.algorithm.is_none() || self.algorithm == Some(algorithm));
+
+        let sha256 = self.sha256.result();
+        let mut hotp = match algorithm {
+            Algorithm::SHA1 => HOTP::new(algorithm, self.sha1.result()),
+            Algorithm::SHA256 => HOTP::new(algorithm, sha256),
+            Algorithm::SHA512 => HOTP::new(algorithm, self.sha512.result()),
+        };
+        hotp.fingerprint = fingerprint(&sha256);
+        hotp
+    }
+}
+
+#[cfg(feature = "zeroize")]
+///Overwrites memory of `value` with zeros in a way that is not optimized out.
+///
+///## Safety
+///
+///All zero bytes must be valid value of `T` and `T` must not own heap memory.
+///
+///For `lhash` types this relies on their private layout (`lhash` 1.1.0: digests and `HmacKey` consist of
+///integer arrays and counters only), hence `lhash` is pinned to exact version, which must be re-checked on upgrade.
+unsafe fn wipe<T>(value: &mut T) {
+    use core::ptr;
+    use core::sync::atomic::{compiler_fence, Ordering};
+
+    let ptr = value as *mut T as *mut u8;
+    for idx in 0..mem::size_of::<T>() {
+        ptr::write_volatile(ptr.add(idx), 0);
+    }
+    compiler_fence(Ordering::SeqCst);
+}
+
+#[cfg(all(feature = "zeroize", feature = "alloc"))]
+///Overwrites `bytes` with zeros in a way that is not optimized out.
+pub(crate) fn wipe_bytes(bytes: &mut [u8]) {
+    use core::sync::atomic::{compiler_fence, Ordering};
+
+    for byte in bytes.iter_mut() {
+        //SAFETY: `byte` is valid exclusive reference.
+        unsafe {
+            core::ptr::write_volatile(byte, 0);
+        }
+    }
+    compiler_fence(Ordering::SeqCst);
+}
+
+impl Default for SecretAccumulator {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for SecretAccumulator {
+    fn drop(&mut self) {
+        //SAFETY: digests of `lhash` 1.1 consist of integer arrays and counters only, refer to `wipe`.
+        unsafe {
+            wipe(&mut self.buffer);
+            wipe(&mut self.sha1);
+            wipe(&mut self.sha256);
+            wipe(&mut self.sha512);
+        }
+    }
+}
+
+///Accumulates secret produced byte by byte via `decode`, passing it to accumulator in chunks.
+fn accumulate_secret<E, D: FnOnce(&mut dyn FnMut(u8)) -> Result<(), E>>(algorithm: Algorithm, decode: D) -> Result<SecretAccumulator, E> {
+    let mut secret = SecretAccumulator::for_algorithm(Some(algorithm));
+    let mut chunk = [0u8; 64];
+    let mut chunk_len = 0;
+
+    let result = decode(&mut |byte| {
+        chunk[chunk_len] = byte;
+        chunk_len += 1;
+        if chunk_len == chunk.len() {
+            secret.update(&chunk);
+            chunk_len = 0;
+        }
+    });
+    secret.update(&chunk[..chunk_len]);
+
+    #[cfg(feature = "zeroize")]
+    //SAFETY: byte array has no invalid values nor heap memory.
+    unsafe {
+        wipe(&mut chunk);
+    }
+
+    result.map(|_| secret)
+}
+
+#[inline]
+fn fingerprint(hash: &[u8; 32]) -> [u8; 8] {
+    let mut fingerprint = [0u8; 8];
+    fingerprint.copy_from_slice(&hash[..8]);
+    fingerprint
+}
+
 ///HMAC based OTP algorithm that uses simple counter as input.
+///
+///Raw secret is not retained, unless `retain-secret` feature is enabled in order to export it via `to_uri`.
+///
+///With `zeroize` feature HMAC key and retained secret are overwritten with zeros on drop.
+///Note that it cannot wipe copies made by the compiler when value is moved, nor memory of the
+///secret provided by user, which remains user's responsibility.
 pub struct HOTP {
     ///HMAC key generated using `algorithm` and `secret`
-    key: HmacKey
+    key: HmacKey,
+    ///Truncated hash of `secret`
+    fingerprint: [u8; 8],
+    #[cfg(feature = "retain-secret")]
+    secret: alloc::vec::Vec<u8>,
 }
 
 impl HOTP {
@@ -38,7 +337,14 @@ impl HOTP {
     ///
     ///- `algorithm` - Generally acceptable are HMAC based on `sha-1`, `sha-256` and `sha-512`
     ///- `secret` - Raw bytes used to derive HMAC key. User is responsible to decode it before
-    ///passing.
+    ///  passing. Must not be empty, which is only checked in debug builds. Use `try_new` to validate it.
+    ///
+    ///```
+    ///use otpshka::{Algorithm, HOTP};
+    ///
+    ///let hotp = HOTP::new(Algorithm::SHA256, b"12345678901234567890123456789012");
+    ///assert!(hotp.verify("46119246", 1));
+    ///```
     pub fn new<T: AsRef<[u8]>>(algorithm: Algorithm, secret: T) -> Self {
         let secret = secret.as_ref();
         debug_assert_ne!(secret.len(), 0);
@@ -48,13 +354,120 @@ impl HOTP {
                 Algorithm::SHA1 => HmacKey::Sha1(lhash::HmacKey::new(secret)),
                 Algorithm::SHA256 => HmacKey::Sha256(lhash::HmacKey::new(secret)),
                 Algorithm::SHA512 => HmacKey::Sha512(lhash::HmacKey::new(secret)),
-            }
+            },
+            fingerprint: fingerprint(&lhash::sha256(secret)),
+            #[cfg(feature = "retain-secret")]
+            secret: secret.to_vec(),
         }
     }
 
+    #[inline]
+    ///Returns HMAC algorithm of the key.
+    pub const fn algorithm(&self) -> Algorithm {
+        match self.key {
+            HmacKey::Sha1(_) => Algorithm::SHA1,
+            HmacKey::Sha256(_) => Algorithm::SHA256,
+            HmacKey::Sha512(_) => Algorithm::SHA512,
+        }
+    }
+
+    #[cfg(feature = "retain-secret")]
+    #[inline(always)]
+    ///Returns raw secret.
+    pub(crate) fn secret(&self) -> &[u8] {
+        &self.secret
+    }
+
+    #[cfg(feature = "retain-secret")]
+    ///Creates `otpauth://hotp/` URI, which can be used to provision account (e.g. via QR code).
+    ///
+    ///- `label` - Name of account (e.g. user name or email).
+    ///- `issuer` - Optional name of service, which is prepended to label and added as parameter.
+    ///- `digits` - Number of digits in password.
+    ///- `counter` - Initial value of counter.
+    ///
+    ///Secret is encoded as base32 without padding, while label and issuer are percent-encoded.
+    pub fn to_uri(&self, label: &str, issuer: Option<&str>, digits: u8, counter: u64) -> alloc::string::String {
+        use core::fmt::Write;
+
+        let mut uri = crate::uri::start_uri("hotp", issuer, label, self.secret(), self.algorithm(), digits);
+        let _ = write!(uri, "&counter={}", counter);
+        uri
+    }
+
+    #[cfg(feature = "alloc")]
+    ///Parses `otpauth://hotp/` URI, returning `HOTP` with number of digits and initial counter.
+    ///
+    ///`secret` and `counter` are required, while `algorithm` and `digits` fall back to SHA-1 and 6.
+    pub fn from_uri(uri: &str) -> Result<(Self, u8, u64), crate::ParseUriError> {
+        let (account, counter) = crate::Account::parse_uri(uri, "hotp")?;
+        match counter {
+            Some(counter) => Ok((Self::new(account.algorithm, &account.secret), account.digits, counter)),
+            None => Err(crate::ParseUriError::InvalidCounter),
+        }
+    }
+
+    #[inline]
+    ///Initializes algorithm using provided `algorithm` and `secret`, returning error on empty `secret`.
+    ///
+    ///Use it when secret comes from external source (e.g. database).
+    pub fn try_new<T: AsRef<[u8]>>(algorithm: Algorithm, secret: T) -> Result<Self, SecretError> {
+        let secret = secret.as_ref();
+        if secret.is_empty() {
+            Err(SecretError::Empty)
+        } else {
+            Ok(Self::new(algorithm, secret))
+        }
+    }
+
+    ///Initializes algorithm using provided `algorithm` and base32 `encoded` secret.
+    ///
+    ///Decoding follows RFC 4648: it is case insensitive and ignores ASCII whitespace, while padding is optional.
+    pub fn from_base32<T: AsRef<[u8]>>(algorithm: Algorithm, encoded: T) -> Result<Self, Base32Error> {
+        let secret = accumulate_secret(algorithm, |out| crate::base32::decode(encoded.as_ref(), out))?;
+
+        match secret.len {
+            0 => Err(Base32Error::Empty),
+            _ => Ok(secret.finish(algorithm)),
+        }
+    }
+
+    ///Initializes algorithm using provided `algorithm` and hex `encoded` secret.
+    ///
+    ///Decoding is case insensitive.
+    pub fn from_hex<T: AsRef<[u8]>>(algorithm: Algorithm, encoded: T) -> Result<Self, HexError> {
+        let secret = accumulate_secret(algorithm, |out| crate::hex::decode(encoded.as_ref(), out))?;
+
+        match secret.len {
+            0 => Err(HexError::Empty),
+            _ => Ok(secret.finish(algorithm)),
+        }
+    }
+
+    #[inline(always)]
+    ///Returns fingerprint of the secret, which is first 8 bytes of its `sha-256` hash.
+    ///
+    ///It is not reversible and can be used to tell accounts apart in logs without exposing secret.
+    ///Nevertheless it identifies secret, so handle it with care (e.g. do not expose it to users).
+    pub const fn key_fingerprint(&self) -> [u8; 8] {
+        self.fingerprint
+    }
+
+    #[inline(always)]
+    ///Starts accumulating secret, which is provided in chunks.
+    ///
+    ///Prefer `new` when whole secret is available.
+    pub const fn builder_secret() -> SecretAccumulator {
+        SecretAccumulator::new()
+    }
+
     #[inline]
     ///Signs provided `counter` value using stored HMAC key.
-    pub fn sign(&self, counter: u64) -> impl AsRef<[u8]> + Clone + Copy {
+    ///
+    ///Key block is derived from secret once, on creation of `HOTP`, but every call still hashes
+    ///inner and outer key blocks, as `lhash` does not allow to retain intermediate digest state.
+    ///Hence cost grows linearly with number of signed counters (e.g. windows within skew).
+    pub fn sign(&self, counter: u64) -> impl AsRef<[u8]> + Copy {
         let counter = counter.to_be_bytes();
 
         match self.key {
@@ -64,80 +477,720 @@ impl HOTP {
         }
     }
 
-    ///Generates password as number from provided `counter` value with length of `digits`.
+    #[cfg(feature = "prf")]
+    ///Derives pseudorandom value from stored HMAC key and provided `label`.
     ///
-    ///Note that in this case you must handle missing padding yourself.
-    pub fn generate_num(&self, counter: u64, digits: u8) -> u32 {
-        const BASE: u32 = 10;
+    ///Output is HMAC tag of `label` prefixed with domain separator, hence it never collides with
+    ///`sign` output for any counter.
+    ///Tag is written at the start of returned buffer and its length depends on algorithm (20 bytes for `sha-1`,
+    ///32 for `sha-256` and 64 for `sha-512`), while remaining bytes are zero.
+    ///
+    ///Note that this re-uses OTP key for different purpose, so make sure anything derived this way
+    ///is not more valuable than the key itself. Use distinct `label` for each purpose.
+    pub fn prf(&self, label: &[u8]) -> [u8; 64] {
+        let tag = match self.key {
+            HmacKey::Sha1(ref key) => HmacOutput::Sha1(prf_sign(key, label)),
+            HmacKey::Sha256(ref key) => HmacOutput::Sha256(prf_sign(key, label)),
+            HmacKey::Sha512(ref key) => HmacOutput::Sha512(prf_sign(key, label)),
+        };
 
-        let sign = self.sign(counter);
-        let sign = sign.as_ref();
+        tag_to_array(tag.as_ref()).0
+    }
+
+    ///Signs provided `counter` value using stored HMAC key, returning result as fixed array.
+    ///
+    ///Tag is written at the start of array and its length is `Algorithm::digest_len` of returned algorithm.
+    ///Remaining bytes are zero.
+    pub fn sign_array(&self, counter: u64) -> (Algorithm, [u8; 64]) {
+        (self.algorithm(), tag_to_array(self.sign(counter).as_ref()).0)
+    }
+
+    #[inline]
+    ///Returns HMAC of the 8-byte big-endian `counter`, which is the first step of generating password.
+    ///
+    ///Tag is written at the start of returned buffer, followed by zeroes, and its length is returned alongside.
+    ///This is intended for comparing against intermediate values of published test vectors.
+    pub fn hmac_counter(&self, counter: u64) -> ([u8; 64], usize) {
+        tag_to_array(self.sign(counter).as_ref())
+    }
+
+    ///Signs provided `counter` together with server provided `nonce`, using stored HMAC key.
+    ///
+    ///Input of HMAC is domain separator `otpshka.nonce:`, followed by 8-byte big-endian `counter`,
+    ///length of `nonce` as single byte and `nonce` itself. Hence it never collides with `sign` output
+    ///for any counter nor with `prf` output for any label.
+    ///Tag is written at the start of returned buffer, followed by zeroes, and its length is returned alongside.
+    ///
+    ///This is non-standard extension, which binds password to the challenge (e.g. session),
+    ///so that phished password cannot be used with a different challenge.
+    ///Both sides must support it.
+    ///
+    ///## Panics
+    ///
+    ///If `nonce` is empty or longer than `MAX_NONCE_LEN`
+    pub fn sign_counter_with_nonce(&self, counter: u64, nonce: &[u8]) -> ([u8; 64], usize) {
+        const COUNTER_START: usize = NONCE_DOMAIN.len();
+        const LEN_START: usize = COUNTER_START + mem::size_of::<u64>();
+        const NONCE_START: usize = LEN_START + 1;
 
-        let offset = (sign[sign.len() - 1] & 15) as usize;
-        debug_assert!(offset + mem::size_of::<u32>() < sign.len());
+        assert!(!nonce.is_empty(), "nonce cannot be empty");
+        assert!(nonce.len() <= MAX_NONCE_LEN, "nonce cannot exceed MAX_NONCE_LEN");
 
-        let snum = unsafe {
-            let mut snum = mem::MaybeUninit::<u32>::uninit();
-            ptr::copy_nonoverlapping(sign.as_ptr().add(offset), snum.as_mut_ptr() as _, 4);
-            snum.assume_init().to_be() & 0x7fff_ffff
+        let mut input = [0u8; NONCE_START + MAX_NONCE_LEN];
+        input[..COUNTER_START].copy_from_slice(NONCE_DOMAIN);
+        input[COUNTER_START..LEN_START].copy_from_slice(&counter.to_be_bytes());
+        input[LEN_START] = nonce.len() as u8;
+        input[NONCE_START..NONCE_START + nonce.len()].copy_from_slice(nonce);
+        let input = &input[..NONCE_START + nonce.len()];
+
+        let tag = match self.key {
+            HmacKey::Sha1(ref key) => HmacOutput::Sha1(key.sign(input)),
+            HmacKey::Sha256(ref key) => HmacOutput::Sha256(key.sign(input)),
+            HmacKey::Sha512(ref key) => HmacOutput::Sha512(key.sign(input)),
         };
 
-        snum % BASE.pow(digits as u32)
+        tag_to_array(tag.as_ref())
     }
 
-    unsafe fn generate_to_ptr(&self, counter: u64, dest: *mut u8, len: usize) {
-        use core::fmt::{self, Write};
-
-        struct WriteBuffer(*mut u8, usize);
-        impl Write for WriteBuffer {
-            #[inline]
-            fn write_str(&mut self, text: &str) -> fmt::Result {
-                //write! can call it multiple times hence remember
-                let written_len = text.len();
-                debug_assert!(written_len <= self.1);
+    #[inline]
+    ///Generates password as number from provided `counter` and `nonce` with length of `digits`.
+    ///
+    ///Refer to `sign_counter_with_nonce` for details.
+    ///
+    ///## Panics
+    ///
+    ///If `nonce` is empty or longer than `MAX_NONCE_LEN`
+    pub fn generate_num_with_nonce(&self, counter: u64, nonce: &[u8], digits: u8) -> u32 {
+        let (tag, len) = self.sign_counter_with_nonce(counter, nonce);
+        truncate_num(dynamic_truncate(&tag[..len]), digits)
+    }
 
-                unsafe {
-                    ptr::copy_nonoverlapping(text.as_ptr(), self.0, written_len);
-                    self.0 = self.0.add(written_len);
-                }
-                self.1 = self.1 - written_len;
+    ///Checks whether provided `token` corresponds to `counter` and `nonce`.
+    ///
+    ///Refer to `sign_counter_with_nonce` for details.
+    ///Unlike generation, empty `nonce` or one longer than `MAX_NONCE_LEN` is rejected instead of panicking.
+    pub fn verify_with_nonce(&self, token: &str, counter: u64, nonce: &[u8]) -> bool {
+        if nonce.is_empty() || nonce.len() > MAX_NONCE_LEN {
+            return false;
+        }
 
-                Ok(())
-            }
+        match parse_token(token) {
+            Some(token) => token.matches(self.generate_num_with_nonce(counter, nonce, token.digits())),
+            None => false,
         }
+    }
 
-        debug_assert_ne!(len, 0);
-        debug_assert!(len <= u8::max_value() as _);
-        debug_assert!(!dest.is_null());
+    ///Returns result of dynamic truncation (RFC 4226, section 5.3) for provided `counter` value.
+    ///
+    ///This is 31-bit number before reduction to decimal digits, which can be used to implement
+    ///custom token encoding (e.g. `generate_steam`).
+    pub fn truncate(&self, counter: u64) -> u32 {
+        let sign = self.sign(counter);
+        dynamic_truncate(sign.as_ref())
+    }
 
-        let snum = self.generate_num(counter, len as u8);
+    ///Generates password as number from provided `counter` value with length of `digits`.
+    ///
+    ///Note that in this case you must handle missing padding yourself.
+    ///`digits` above `MAX_DIGITS` produce the same value as `MAX_DIGITS`.
+    pub fn generate_num(&self, counter: u64, digits: u8) -> u32 {
+        truncate_num(self.truncate(counter), digits)
+    }
+
+    ///Generates Steam Guard code from provided `counter` value.
+    ///
+    ///It uses the same dynamic truncation as `generate_num`, but encodes result as 5 characters of
+    ///Steam alphabet instead of decimal digits.
+    ///Steam uses `sha-1` with 30 seconds window, so it is normally used via `TOTP::generate_steam`.
+    pub fn generate_steam(&self, counter: u64) -> [u8; 5] {
+        let mut code = self.truncate(counter) as usize;
 
-        let mut buffer = WriteBuffer(dest, len);
-        let _ = write!(buffer, "{:0width$}", snum, width = len);
+        let mut result = [0u8; 5];
+        for ch in result.iter_mut() {
+            *ch = STEAM_ALPHABET[code % STEAM_ALPHABET.len()];
+            code /= STEAM_ALPHABET.len();
+        }
+        result
+    }
+
+    #[cfg(feature = "alloc")]
+    #[inline]
+    ///Generates password from provided `counter` value with length of `digits`, padded with zeros.
+    pub fn generate(&self, counter: u64, digits: u8) -> alloc::string::String {
+        alloc::format!("{:0width$}", self.generate_num(counter, digits), width = digits as usize)
+    }
+
+    #[inline]
+    ///Generates password as number from provided `counter` value with length of `digits`.
+    ///
+    ///Unlike `generate_num` it returns error when `digits` is zero or exceeds `MAX_DIGITS`.
+    pub fn try_generate_num(&self, counter: u64, digits: u8) -> Result<u32, DigitsError> {
+        match digits {
+            0 => Err(DigitsError::Zero),
+            digits if digits > MAX_DIGITS => Err(DigitsError::TooMany),
+            digits => Ok(self.generate_num(counter, digits)),
+        }
     }
 
     #[inline]
     ///Generates password based on provided `counter` value and writes it into provided `dest`.
     ///
-    ///This always writes `dest.as_ref().len()`.
+    ///This always writes `dest.as_ref().len()`, which is number of digits and must be within `1..=MAX_DIGITS`.
     ///
-    ///Recommended buffer length is be within `6..8`
+    ///## Panics
+    ///
+    ///In debug builds, if length of `dest` is outside of `1..=MAX_DIGITS`
     pub fn generate_to<T: AsMut<[u8]>>(&self, counter: u64, mut dest: T) {
         let dest = dest.as_mut();
-        unsafe {
-            self.generate_to_ptr(counter, dest.as_mut_ptr(), dest.len())
+        debug_assert_ne!(dest.len(), 0);
+        debug_assert!(dest.len() <= MAX_DIGITS as usize, "Number of digits cannot exceed MAX_DIGITS");
+
+        let mut snum = self.generate_num(counter, dest.len() as u8);
+        for digit in dest.iter_mut().rev() {
+            *digit = b'0' + (snum % 10) as u8;
+            snum /= 10;
         }
     }
 
+    #[inline]
+    ///Generates password based on provided `counter` value as array of `N` ASCII digits.
+    ///
+    ///Number of digits is `N`, which must be within `1..=MAX_DIGITS`.
+    ///
+    ///## Panics
+    ///
+    ///In debug builds, if `N` is outside of `1..=MAX_DIGITS`
+    pub fn generate_to_array<const N: usize>(&self, counter: u64) -> [u8; N] {
+        let mut dest = [0u8; N];
+        self.generate_to(counter, &mut dest);
+        dest
+    }
+
     ///Checks whether provided `token` corresponds to `counter`.
+    ///
+    ///Number of digits is derived from `token`, which must consist of digits only (refer to `unicode` feature).
     pub fn verify(&self, token: &str, counter: u64) -> bool {
-        debug_assert!(token.len() <= u8::max_value() as _);
+        match parse_token(token) {
+            Some(token) => token.matches(self.generate_num(counter, token.digits())),
+            None => false,
+        }
+    }
 
-        let expected = match u32::from_str_radix(token, 10) {
-            Ok(expected) => expected,
-            Err(_) => return false,
-        };
+    ///Checks whether provided `token` has exactly `expected_digits` and corresponds to `counter`.
+    ///
+    ///Unlike `verify`, number of digits is not derived from `token`, so shorter token cannot be
+    ///accepted when longer is expected. Token of mismatching length is rejected without computing HMAC.
+    pub fn verify_digits(&self, token: &str, counter: u64, expected_digits: u8) -> bool {
+        match parse_token(token) {
+            Some(token) if token.digits() == expected_digits => token.matches(self.generate_num(counter, expected_digits)),
+            _ => false,
+        }
+    }
+
+    ///Checks provided `token` against counters `counter..=counter + ahead`, returning the first one that matched.
+    ///
+    ///This is resynchronization as described in RFC 4226 section 7.4: on success caller should
+    ///store matched counter plus one as the next expected counter.
+    ///Keep `ahead` small, as each extra counter increases chance of accepting guessed token.
+    pub fn verify_with_window(&self, token: &str, counter: u64, ahead: u64) -> Option<u64> {
+        self.verify_batch(token, counter..=counter.saturating_add(ahead))
+    }
+
+    ///Checks provided `token` against each of `counters` in order, returning the first one that matched.
+    ///
+    ///Counters can be sparse (e.g. candidates from lossy queue of events).
+    ///Each candidate is compared in constant time, same as in `verify`, and no allocation is performed.
+    pub fn verify_batch<I: IntoIterator<Item = u64>>(&self, token: &str, counters: I) -> Option<u64> {
+        let token = parse_token(token)?;
+        counters.into_iter().find(|counter| token.matches(self.generate_num(*counter, token.digits())))
+    }
+}
+
+impl fmt::Debug for HOTP {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("HOTP")
+           .field("algorithm", &self.algorithm())
+           .field("key", &format_args!("<redacted>"))
+           .finish()
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for HOTP {
+    fn drop(&mut self) {
+        //SAFETY: `lhash::HmacKey` of `lhash` 1.1 consists of key block only, which is byte array.
+        //This is private layout of `lhash`, refer to `wipe`.
+        unsafe {
+            match self.key {
+                HmacKey::Sha1(ref mut key) => wipe(key),
+                HmacKey::Sha256(ref mut key) => wipe(key),
+                HmacKey::Sha512(ref mut key) => wipe(key),
+            }
+        }
+
+        #[cfg(feature = "retain-secret")]
+        wipe_bytes(&mut self.secret);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RFC_SECRET: &[u8] = b"12345678901234567890";
+
+    #[test]
+    fn should_test_hotp_rfc4226() {
+        let input = [
+            "755224", "287082", "359152", "969429", "338314",
+            "254676", "287922", "162583", "399871", "520489",
+        ];
+
+        let hotp = HOTP::new(Algorithm::SHA1, RFC_SECRET);
+
+        for (counter, expected) in input.iter().enumerate() {
+            let mut output = [0u8; 6];
+            hotp.generate_to(counter as u64, &mut output[..]);
+            let token = core::str::from_utf8(&output).expect("UTF-8 compatible output");
+            assert_eq!(token, *expected);
+            assert!(hotp.verify(token, counter as u64));
+            assert!(!hotp.verify(token, counter as u64 + 1));
+        }
+    }
+
+    #[test]
+    fn should_test_hotp_rfc4226_long_tokens() {
+        //Truncated values from RFC 4226 Appendix D
+        let input = [
+            "1284755224", "1094287082", "0137359152", "1726969429", "1640338314",
+            "0868254676", "1918287922", "0082162583", "0673399871", "0645520489",
+        ];
+
+        let hotp = HOTP::new(Algorithm::SHA1, RFC_SECRET);
+
+        for (counter, expected) in input.iter().enumerate() {
+            let mut output = [0u8; 10];
+            hotp.generate_to(counter as u64, &mut output[..]);
+            let token = core::str::from_utf8(&output).expect("UTF-8 compatible output");
+            assert_eq!(token, *expected);
+            assert!(hotp.verify(token, counter as u64));
+            assert!(!hotp.verify(token, counter as u64 + 1));
+
+            let mut output = [0u8; 8];
+            hotp.generate_to(counter as u64, &mut output[..]);
+            let token = core::str::from_utf8(&output).expect("UTF-8 compatible output");
+            assert_eq!(token, &expected[2..]);
+            assert!(hotp.verify(token, counter as u64));
+            assert!(!hotp.verify(token, counter as u64 + 1));
+        }
+    }
+
+    #[cfg(feature = "retain-secret")]
+    #[test]
+    fn should_create_uri() {
+        let hotp = HOTP::new(Algorithm::SHA1, RFC_SECRET);
+        assert_eq!(hotp.to_uri("alice@example.com", None, 6, 0), "otpauth://hotp/alice%40example.com?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&algorithm=SHA1&digits=6&counter=0");
+
+        let hotp = HOTP::new(Algorithm::SHA512, RFC_SECRET);
+        assert_eq!(hotp.to_uri("alice@example.com", Some("Big Corp"), 8, 42), "otpauth://hotp/Big%20Corp:alice%40example.com?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&issuer=Big%20Corp&algorithm=SHA512&digits=8&counter=42");
+    }
+
+    #[cfg(feature = "retain-secret")]
+    #[test]
+    fn should_round_trip_uri() {
+        let hotp = HOTP::new(Algorithm::SHA256, RFC_SECRET);
+        let uri = hotp.to_uri("alice", Some("Big Corp"), 8, 42);
+        let (parsed, digits, counter) = HOTP::from_uri(&uri).expect("to parse");
+        assert_eq!(digits, 8);
+        assert_eq!(counter, 42);
+        assert_eq!(parsed.to_uri("alice", Some("Big Corp"), digits, counter), uri);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn should_parse_uri() {
+        use crate::ParseUriError;
+
+        let (parsed, digits, counter) = HOTP::from_uri("otpauth://hotp/alice?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&counter=1").expect("to parse");
+        assert_eq!(digits, 6);
+        assert_eq!(counter, 1);
+        assert!(parsed.verify("287082", counter));
+
+        assert_eq!(HOTP::from_uri("otpauth://hotp/alice?secret=GEZDGNBV").err(), Some(ParseUriError::InvalidCounter));
+        assert_eq!(HOTP::from_uri("otpauth://hotp/alice?secret=GEZDGNBV&counter=-1").err(), Some(ParseUriError::InvalidCounter));
+        assert_eq!(HOTP::from_uri("otpauth://totp/alice?secret=GEZDGNBV&counter=1").err(), Some(ParseUriError::UnsupportedType));
+    }
+
+    #[test]
+    fn should_create_from_long_encoded_secret() {
+        let mut secret = [0u8; 200];
+        let mut hex = [0u8; 400];
+        for (idx, byte) in secret.iter_mut().enumerate() {
+            *byte = idx as u8;
+            hex[idx * 2] = b"0123456789abcdef"[idx >> 4];
+            hex[idx * 2 + 1] = b"0123456789abcdef"[idx & 15];
+        }
+        //Each 8 characters of `A` decode to 5 zero bytes
+        let base32 = [b'A'; 400];
+
+        for algorithm in [Algorithm::SHA1, Algorithm::SHA256, Algorithm::SHA512].iter() {
+            for len in [64usize, 65, 128, 129, 200].iter() {
+                let expected = HOTP::new(*algorithm, &secret[..*len]);
+                let hotp = HOTP::from_hex(*algorithm, &hex[..*len * 2]).expect("to decode");
+                assert_eq!(hotp.sign(1).as_ref(), expected.sign(1).as_ref());
+                assert_eq!(hotp.key_fingerprint(), expected.key_fingerprint());
+            }
+
+            let expected = HOTP::new(*algorithm, [0u8; 250]);
+            let hotp = HOTP::from_base32(*algorithm, &base32[..]).expect("to decode");
+            assert_eq!(hotp.sign(1).as_ref(), expected.sign(1).as_ref());
+            assert_eq!(hotp.key_fingerprint(), expected.key_fingerprint());
+        }
+    }
+
+    #[test]
+    fn should_create_from_base32() {
+        let hotp = HOTP::from_base32(Algorithm::SHA1, "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ").expect("to decode");
+        assert!(hotp.verify("755224", 0));
+        assert_eq!(hotp.key_fingerprint(), HOTP::new(Algorithm::SHA1, RFC_SECRET).key_fingerprint());
+
+        let hotp = HOTP::from_base32(Algorithm::SHA1, "gezd gnbv gy3t qojq gezd gnbv gy3t qojq").expect("to decode");
+        assert!(hotp.verify("287082", 1));
+        let hotp = HOTP::from_base32(Algorithm::SHA1, "MZXW6YQ=").expect("to decode");
+        assert_eq!(hotp.key_fingerprint(), HOTP::new(Algorithm::SHA1, b"foob").key_fingerprint());
+
+        assert_eq!(HOTP::from_base32(Algorithm::SHA1, "GEZDGNB1").err(), Some(Base32Error::InvalidCharacter));
+        assert_eq!(HOTP::from_base32(Algorithm::SHA1, "MY==MY").err(), Some(Base32Error::InvalidCharacter));
+        assert_eq!(HOTP::from_base32(Algorithm::SHA1, "").err(), Some(Base32Error::Empty));
+        assert_eq!(HOTP::from_base32(Algorithm::SHA1, " ==").err(), Some(Base32Error::Empty));
+    }
+
+    #[test]
+    fn should_verify_with_look_ahead_window() {
+        let hotp = HOTP::new(Algorithm::SHA1, RFC_SECRET);
+
+        assert_eq!(hotp.verify_with_window("755224", 0, 0), Some(0));
+        assert_eq!(hotp.verify_with_window("338314", 0, 0), None);
+        assert_eq!(hotp.verify_with_window("338314", 0, 3), None);
+        assert_eq!(hotp.verify_with_window("338314", 0, 4), Some(4));
+        assert_eq!(hotp.verify_with_window("338314", 2, 10), Some(4));
+        assert_eq!(hotp.verify_with_window("338314", 5, 10), None);
+        assert_eq!(hotp.verify_with_window("33831a", 0, 10), None);
+        assert_eq!(hotp.verify_with_window("338314", u64::MAX - 1, u64::MAX), None);
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn should_wipe_secret_material() {
+        let mut buffer = [0xaau8; 64];
+        unsafe {
+            wipe(&mut buffer);
+        }
+        assert_eq!(buffer, [0u8; 64]);
+
+        #[cfg(feature = "alloc")]
+        {
+            let mut bytes = [0x55u8; 20];
+            wipe_bytes(&mut bytes);
+            assert_eq!(bytes, [0u8; 20]);
+        }
+
+        let mut secret = HOTP::builder_secret();
+        secret.update(RFC_SECRET);
+        let hotp = secret.finish(Algorithm::SHA1);
+        assert!(hotp.verify("755224", 0));
+        drop(hotp);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn should_generate_padded_string() {
+        let hotp = HOTP::new(Algorithm::SHA1, RFC_SECRET);
+        assert_eq!(hotp.generate(0, 6), "755224");
+        assert_eq!(hotp.generate(2, 10), "0137359152");
+        assert_eq!(hotp.generate(7, 8), "82162583");
+        assert_eq!(hotp.generate(7, 9), "082162583");
+    }
+
+    #[test]
+    fn should_generate_to_array() {
+        let hotp = HOTP::new(Algorithm::SHA1, RFC_SECRET);
+
+        let code: [u8; 6] = hotp.generate_to_array(0);
+        assert_eq!(&code, b"755224");
+        assert_eq!(&hotp.generate_to_array::<8>(7), b"82162583");
+        assert_eq!(&hotp.generate_to_array::<10>(2), b"0137359152");
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic]
+    fn should_reject_too_long_generate_to_buffer() {
+        let hotp = HOTP::new(Algorithm::SHA1, RFC_SECRET);
+        hotp.generate_to(0, [0u8; MAX_DIGITS as usize + 1]);
+    }
+
+    #[test]
+    fn should_verify_expected_digits() {
+        let hotp = HOTP::new(Algorithm::SHA1, RFC_SECRET);
+
+        assert!(hotp.verify_digits("755224", 0, 6));
+        assert!(hotp.verify_digits("84755224", 0, 8));
+        assert!(!hotp.verify_digits("755224", 0, 8));
+        assert!(!hotp.verify_digits("84755224", 0, 6));
+        assert!(!hotp.verify_digits("+755224", 0, 7));
+        assert!(!hotp.verify_digits(" 755224", 0, 7));
+        assert!(!hotp.verify_digits("", 0, 0));
+    }
+
+    #[test]
+    fn should_return_algorithm() {
+        assert_eq!(HOTP::new(Algorithm::SHA1, RFC_SECRET).algorithm(), Algorithm::SHA1);
+        assert_eq!(HOTP::new(Algorithm::SHA256, RFC_SECRET).algorithm(), Algorithm::SHA256);
+        assert_eq!(HOTP::new(Algorithm::SHA512, RFC_SECRET).algorithm(), Algorithm::SHA512);
+    }
+
+    #[test]
+    fn should_generate_steam() {
+        let hotp = HOTP::new(Algorithm::SHA1, RFC_SECRET);
+        assert_eq!(&hotp.generate_steam(0), b"GG5F5");
+        assert_eq!(&hotp.generate_steam(1), b"PV9M4");
+
+        assert!(steam_matches(b"GG5F5", &hotp.generate_steam(0)));
+        assert!(steam_matches(b"gg5f5", &hotp.generate_steam(0)));
+        assert!(!steam_matches(b"GG5F4", &hotp.generate_steam(0)));
+        assert!(!steam_matches(b"GG5F", &hotp.generate_steam(0)));
+        //Published vector of steamguard-cli (`steamguard` crate, `token::tests::test_generate_code`):
+        //shared secret `zvIayp3JPvtvX/QGHqsqKBk/44s=` (base64) at time 1616374841 is `2F9J5`
+        let hotp = HOTP::new(Algorithm::SHA1, [206, 242, 26, 202, 157, 201, 62, 251, 111, 95, 244, 6, 30, 171, 42, 40, 25, 63, 227, 139]);
+        assert_eq!(&hotp.generate_steam(1616374841 / 30), b"2F9J5");
+    }
+
+    #[test]
+    fn should_verify_batch() {
+        let hotp = HOTP::new(Algorithm::SHA1, RFC_SECRET);
+
+        assert_eq!(hotp.verify_batch("338314", 0..10), Some(4));
+        assert_eq!(hotp.verify_batch("338314", [1, 3, 4, 9].iter().copied()), Some(4));
+        assert_eq!(hotp.verify_batch("338314", [1, 3, 9]), None);
+        assert_eq!(hotp.verify_batch("338314", core::iter::empty()), None);
+        assert_eq!(hotp.verify_batch("33831a", 0..10), None);
+    }
+
+    #[test]
+    fn should_create_from_hex() {
+        let hotp = HOTP::from_hex(Algorithm::SHA1, "3132333435363738393031323334353637383930").expect("to decode");
+        assert!(hotp.verify("755224", 0));
+        assert_eq!(hotp.key_fingerprint(), HOTP::new(Algorithm::SHA1, RFC_SECRET).key_fingerprint());
+
+        assert_eq!(HOTP::from_hex(Algorithm::SHA1, "313").err(), Some(HexError::OddLength));
+        assert_eq!(HOTP::from_hex(Algorithm::SHA1, "31zz").err(), Some(HexError::InvalidCharacter));
+        assert_eq!(HOTP::from_hex(Algorithm::SHA1, "").err(), Some(HexError::Empty));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn should_redact_debug() {
+        let hotp = HOTP::new(Algorithm::SHA256, RFC_SECRET);
+        let output = alloc::format!("{:?}", hotp);
+        assert_eq!(output, "HOTP { algorithm: SHA256, key: <redacted> }");
+        assert!(!output.contains("1234"));
+        assert!(!output.contains("49, 50"));
+    }
+
+    #[test]
+    fn should_truncate() {
+        let hotp = HOTP::new(Algorithm::SHA1, RFC_SECRET);
+        //RFC 4226, Appendix D
+        let expected = [1284755224, 1094287082, 137359152, 1726969429, 1640338314, 868254676, 1918287922, 82162583, 673399871, 645520489];
+        for (counter, expected) in expected.iter().enumerate() {
+            let truncated = hotp.truncate(counter as u64);
+            assert_eq!(truncated, *expected);
+            assert!(truncated <= 0x7fff_ffff);
+            assert_eq!(hotp.generate_num(counter as u64, 6), truncated % 1_000_000);
+        }
+    }
+
+    #[test]
+    fn should_try_new() {
+        let hotp = HOTP::try_new(Algorithm::SHA1, RFC_SECRET).expect("to create");
+        assert!(hotp.verify("755224", 0));
+        assert_eq!(HOTP::try_new(Algorithm::SHA1, b"").err(), Some(SecretError::Empty));
+    }
+
+    #[test]
+    fn should_try_generate_num() {
+        let hotp = HOTP::new(Algorithm::SHA1, RFC_SECRET);
+
+        assert_eq!(hotp.try_generate_num(0, 6), Ok(755224));
+        assert_eq!(hotp.try_generate_num(0, MAX_DIGITS), Ok(hotp.generate_num(0, MAX_DIGITS)));
+        assert_eq!(hotp.try_generate_num(0, MAX_DIGITS + 1), Err(DigitsError::TooMany));
+        assert_eq!(hotp.try_generate_num(0, u8::MAX), Err(DigitsError::TooMany));
+        assert_eq!(hotp.try_generate_num(0, 0), Err(DigitsError::Zero));
+        assert_eq!(hotp.try_generate_num(0, 1), Ok(4));
+    }
+
+    #[test]
+    fn should_cap_generate_num_digits() {
+        let hotp = HOTP::new(Algorithm::SHA1, RFC_SECRET);
+        let full = hotp.truncate(0);
+
+        assert_eq!(hotp.generate_num(0, MAX_DIGITS), full);
+        assert_eq!(hotp.generate_num(0, 20), full);
+        assert_eq!(hotp.generate_num(0, 64), full);
+        assert_eq!(hotp.generate_num(0, u8::MAX), full);
+    }
+
+    #[test]
+    fn should_accumulate_secret() {
+        let mut long_secret = [0u8; 200];
+        for (idx, byte) in long_secret.iter_mut().enumerate() {
+            *byte = idx as u8;
+        }
+
+        for secret in [RFC_SECRET, &long_secret[..64], &long_secret[..65], &long_secret[..128], &long_secret[..129], &long_secret[..]].iter() {
+            for algorithm in [Algorithm::SHA1, Algorithm::SHA256, Algorithm::SHA512].iter() {
+                let expected = HOTP::new(*algorithm, secret);
+
+                let mut accumulator = HOTP::builder_secret();
+                for chunk in secret.chunks(7) {
+                    accumulator.update(chunk);
+                }
+                let hotp = accumulator.finish(*algorithm);
+
+                assert_eq!(hotp.sign(1).as_ref(), expected.sign(1).as_ref());
+            }
+        }
+    }
+
+    #[test]
+    fn should_fingerprint_secret() {
+        let hotp = HOTP::new(Algorithm::SHA1, RFC_SECRET);
+        assert_eq!(hotp.key_fingerprint(), HOTP::new(Algorithm::SHA512, RFC_SECRET).key_fingerprint());
+        assert_eq!(hotp.key_fingerprint()[..], lhash::sha256(RFC_SECRET)[..8]);
+        assert_ne!(hotp.key_fingerprint(), HOTP::new(Algorithm::SHA1, b"12345678901234567891").key_fingerprint());
+
+        let long_secret = [1u8; 200];
+        let mut accumulator = HOTP::builder_secret();
+        accumulator.update(&long_secret);
+        assert_eq!(accumulator.finish(Algorithm::SHA1).key_fingerprint(), HOTP::new(Algorithm::SHA1, long_secret).key_fingerprint());
+    }
+
+    #[test]
+    fn should_sign_to_array() {
+        for algorithm in [Algorithm::SHA1, Algorithm::SHA256, Algorithm::SHA512].iter() {
+            let hotp = HOTP::new(*algorithm, RFC_SECRET);
+            let (sign_algorithm, sign) = hotp.sign_array(5);
+
+            assert_eq!(sign_algorithm, *algorithm);
+            assert_eq!(&sign[..algorithm.digest_len()], hotp.sign(5).as_ref());
+            assert!(sign[algorithm.digest_len()..].iter().all(|byte| *byte == 0));
+        }
+    }
+
+    #[test]
+    fn should_test_hmac_counter_rfc4226() {
+        let input: [[u8; 20]; 3] = [
+            [0xcc, 0x93, 0xcf, 0x18, 0x50, 0x8d, 0x94, 0x93, 0x4c, 0x64, 0xb6, 0x5d, 0x8b, 0xa7, 0x66, 0x7f, 0xb7, 0xcd, 0xe4, 0xb0],
+            [0x75, 0xa4, 0x8a, 0x19, 0xd4, 0xcb, 0xe1, 0x00, 0x64, 0x4e, 0x8a, 0xc1, 0x39, 0x7e, 0xea, 0x74, 0x7a, 0x2d, 0x33, 0xab],
+            [0x0b, 0xac, 0xb7, 0xfa, 0x08, 0x2f, 0xef, 0x30, 0x78, 0x22, 0x11, 0x93, 0x8b, 0xc1, 0xc5, 0xe7, 0x04, 0x16, 0xff, 0x44],
+        ];
+
+        let hotp = HOTP::new(Algorithm::SHA1, RFC_SECRET);
+
+        for (counter, expected) in input.iter().enumerate() {
+            let (tag, len) = hotp.hmac_counter(counter as u64);
+            assert_eq!(len, 20);
+            assert_eq!(&tag[..len], expected);
+        }
+    }
+
+    #[test]
+    fn should_sign_counter_with_nonce() {
+        let hotp = HOTP::new(Algorithm::SHA1, RFC_SECRET);
+
+        let (tag, len) = hotp.sign_counter_with_nonce(1, b"session-1");
+        assert_eq!(len, 20);
+        assert_ne!(&tag[..len], hotp.sign(1).as_ref());
+        assert_ne!(tag, hotp.sign_counter_with_nonce(1, b"session-2").0);
+        assert_ne!(tag, hotp.sign_counter_with_nonce(2, b"session-1").0);
+
+        let mut token = [0u8; 6];
+        let code = hotp.generate_num_with_nonce(1, b"session-1", 6);
+        for (idx, digit) in token.iter_mut().enumerate() {
+            *digit = b'0' + (code / 10u32.pow(5 - idx as u32) % 10) as u8;
+        }
+        let token = core::str::from_utf8(&token).expect("UTF-8 compatible output");
+
+        assert!(hotp.verify_with_nonce(token, 1, b"session-1"));
+        assert!(!hotp.verify_with_nonce(token, 2, b"session-1"));
+        assert!(!hotp.verify_with_nonce(token, 1, b"session-2"));
+
+        let nonce = [1u8; MAX_NONCE_LEN];
+        let (_, len) = HOTP::new(Algorithm::SHA512, RFC_SECRET).sign_counter_with_nonce(1, &nonce);
+        assert_eq!(len, 64);
+    }
+
+    #[test]
+    #[should_panic]
+    fn should_reject_empty_nonce() {
+        let hotp = HOTP::new(Algorithm::SHA1, RFC_SECRET);
+        hotp.sign_counter_with_nonce(1, b"");
+    }
+
+    #[test]
+    fn should_separate_nonce_input_from_counter() {
+        let hotp = HOTP::new(Algorithm::SHA1, RFC_SECRET);
+
+        for counter in [0u64, 1, u64::MAX].iter() {
+            let (tag, len) = hotp.sign_counter_with_nonce(*counter, &[0]);
+            assert_ne!(&tag[..len], hotp.sign(*counter).as_ref());
+        }
+
+        //Nonce, which would extend counter bytes into domain separator of other inputs
+        let mut nonce = [0u8; 24];
+        nonce[..4].copy_from_slice(b"prf:");
+        nonce[4..].copy_from_slice(&lhash::sha1(b"device"));
+        let (tag, len) = hotp.sign_counter_with_nonce(u64::from_be_bytes(*b"otpshka."), &nonce);
+        assert_ne!(&tag[..len], hotp.sign(u64::from_be_bytes(*b"otpshka.")).as_ref());
+
+        #[cfg(feature = "prf")]
+        {
+            let prf = hotp.prf(b"device");
+            assert_ne!(tag, prf);
+            assert_ne!(&prf[..20], hotp.sign(u64::from_be_bytes(*b"otpshka.")).as_ref());
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn should_reject_too_long_nonce() {
+        let hotp = HOTP::new(Algorithm::SHA1, RFC_SECRET);
+        hotp.sign_counter_with_nonce(1, &[0u8; MAX_NONCE_LEN + 1]);
+    }
+
+    #[test]
+    fn should_not_verify_invalid_nonce() {
+        let hotp = HOTP::new(Algorithm::SHA1, RFC_SECRET);
+
+        assert!(!hotp.verify_with_nonce("755224", 0, &[0u8; MAX_NONCE_LEN + 1]));
+        assert!(!hotp.verify_with_nonce("755224", 0, b""));
+    }
+
+    #[cfg(feature = "prf")]
+    #[test]
+    fn should_derive_prf() {
+        let hotp = HOTP::new(Algorithm::SHA1, RFC_SECRET);
+
+        let device = hotp.prf(b"device");
+        assert_eq!(device, hotp.prf(b"device"));
+        assert_ne!(device, hotp.prf(b"session"));
+        assert!(device[20..].iter().all(|byte| *byte == 0));
+
+        let counter = u64::from_be_bytes([b'd', b'e', b'v', b'i', b'c', b'e', 0, 0]);
+        assert_ne!(&device[..20], hotp.sign(counter).as_ref());
 
-        self.generate_num(counter, token.len() as u8) == expected
+        let hotp = HOTP::new(Algorithm::SHA512, RFC_SECRET);
+        assert!(hotp.prf(b"device")[20..].iter().any(|byte| *byte != 0));
     }
 }
@@ -0,0 +1,189 @@
+use core::borrow::Borrow;
+
+use crate::hotp::HOTP;
+
+///`HOTP` with counter, which is advanced automatically.
+///
+///Counter must be persisted after every change (refer to `counter`), otherwise client and server get out of sync.
+///
+///Counter `u64::MAX` marks exhaustion: it is never used, so that the last password cannot be generated or accepted twice.
+pub struct HotpCounter<K = HOTP> {
+    inner: K,
+    counter: u64,
+    ///Number of counters after current one that are also accepted by `verify`.
+    ///
+    ///Allows to resynchronize with client that generated codes without submitting them.
+    ///Default is 0.
+    pub look_ahead: u64,
+}
+
+impl<K: Borrow<HOTP>> core::fmt::Debug for HotpCounter<K> {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        fmt.debug_struct("HotpCounter")
+           .field("hotp", self.hotp())
+           .field("counter", &self.counter)
+           .field("look_ahead", &self.look_ahead)
+           .finish()
+    }
+}
+
+impl<K: Borrow<HOTP>> HotpCounter<K> {
+    #[inline]
+    ///Creates new instance starting at `counter`.
+    pub const fn new(inner: K, counter: u64) -> Self {
+        Self {
+            inner,
+            counter,
+            look_ahead: 0,
+        }
+    }
+
+    #[inline(always)]
+    ///Returns underlying `HOTP`.
+    pub fn hotp(&self) -> &HOTP {
+        self.inner.borrow()
+    }
+
+    #[inline(always)]
+    ///Returns counter, which is used for the next password.
+    pub const fn counter(&self) -> u64 {
+        self.counter
+    }
+
+    #[inline(always)]
+    ///Returns whether all counters are used, in which case no password is generated or accepted anymore.
+    pub const fn is_exhausted(&self) -> bool {
+        self.counter == u64::MAX
+    }
+
+    #[inline(always)]
+    ///Sets counter, which is used for the next password (e.g. when restoring persisted state).
+    pub fn set_counter(&mut self, counter: u64) {
+        self.counter = counter;
+    }
+
+    #[inline]
+    ///Generates password for current counter into provided `dest` and increments counter.
+    ///
+    ///Returns `false` without writing `dest` when counter is exhausted.
+    ///Refer to `HOTP::generate_to` for details.
+    pub fn next_to<T: AsMut<[u8]>>(&mut self, dest: T) -> bool {
+        if self.is_exhausted() {
+            return false;
+        }
+
+        self.hotp().generate_to(self.counter, dest);
+        self.counter += 1;
+        true
+    }
+
+    #[inline]
+    #[allow(clippy::should_implement_trait)]
+    ///Generates password of `N` digits for current counter and increments counter.
+    ///
+    ///Returns `None` when counter is exhausted.
+    pub fn next<const N: usize>(&mut self) -> Option<[u8; N]> {
+        let mut dest = [0u8; N];
+        match self.next_to(&mut dest) {
+            true => Some(dest),
+            false => None,
+        }
+    }
+
+    ///Checks whether provided `token` corresponds to current counter or up to `look_ahead` counters after it.
+    ///
+    ///On success counter is advanced past the matched one, so the same `token` is never accepted again.
+    ///Always returns `false` when counter is exhausted.
+    pub fn verify(&mut self, token: &str) -> bool {
+        if self.is_exhausted() {
+            return false;
+        }
+
+        //`u64::MAX` is never checked, as there would be no counter to advance to
+        let ahead = core::cmp::min(self.look_ahead, u64::MAX - 1 - self.counter);
+        match self.hotp().verify_with_window(token, self.counter, ahead) {
+            Some(counter) => {
+                self.counter = counter + 1;
+                true
+            },
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Algorithm;
+
+    const RFC_SECRET: &[u8] = b"12345678901234567890";
+
+    #[test]
+    fn should_generate_and_advance() {
+        let mut hotp = HotpCounter::new(HOTP::new(Algorithm::SHA1, RFC_SECRET), 0);
+
+        assert_eq!(hotp.next::<6>(), Some(*b"755224"));
+        assert_eq!(hotp.next::<6>(), Some(*b"287082"));
+        let mut code = [0u8; 6];
+        assert!(hotp.next_to(&mut code[..]));
+        assert_eq!(&code, b"359152");
+        assert_eq!(hotp.counter(), 3);
+
+        hotp.set_counter(9);
+        assert_eq!(hotp.next::<6>(), Some(*b"520489"));
+        assert_eq!(hotp.counter(), 10);
+    }
+
+    #[test]
+    fn should_stop_at_exhausted_counter() {
+        let hotp = HOTP::new(Algorithm::SHA1, RFC_SECRET);
+        let mut counter = HotpCounter::new(&hotp, u64::MAX - 1);
+        let last = hotp.generate_to_array::<6>(u64::MAX - 1);
+
+        assert!(!counter.is_exhausted());
+        assert_eq!(counter.next::<6>(), Some(last));
+        assert!(counter.is_exhausted());
+        assert_eq!(counter.next::<6>(), None);
+        let mut code = [0u8; 6];
+        assert!(!counter.next_to(&mut code[..]));
+        assert_eq!(code, [0u8; 6]);
+        assert_eq!(counter.counter(), u64::MAX);
+
+        let last = core::str::from_utf8(&last).expect("UTF-8 compatible output");
+        let mut counter = HotpCounter::new(&hotp, u64::MAX - 1);
+        counter.look_ahead = 5;
+        assert!(counter.verify(last));
+        assert!(counter.is_exhausted());
+        assert!(!counter.verify(last));
+
+        //Password of `u64::MAX` itself is never accepted
+        let overflow = hotp.generate_to_array::<6>(u64::MAX);
+        let overflow = core::str::from_utf8(&overflow).expect("UTF-8 compatible output");
+        let mut counter = HotpCounter::new(&hotp, u64::MAX - 1);
+        counter.look_ahead = 5;
+        assert_ne!(overflow, last);
+        assert!(!counter.verify(overflow));
+        assert_eq!(counter.counter(), u64::MAX - 1);
+        counter.set_counter(u64::MAX);
+        assert!(!counter.verify(overflow));
+    }
+
+    #[test]
+    fn should_verify_and_advance() {
+        let hotp = HOTP::new(Algorithm::SHA1, RFC_SECRET);
+        let mut counter = HotpCounter::new(&hotp, 0);
+
+        assert!(counter.verify("755224"));
+        assert_eq!(counter.counter(), 1);
+        assert!(!counter.verify("755224"));
+        assert!(!counter.verify("338314"));
+        assert_eq!(counter.counter(), 1);
+
+        counter.look_ahead = 3;
+        assert!(counter.verify("338314"));
+        assert_eq!(counter.counter(), 5);
+        assert!(!counter.verify("969429"));
+        assert!(counter.verify("254676"));
+        assert_eq!(counter.counter(), 6);
+    }
+}
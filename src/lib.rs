@@ -2,13 +2,21 @@
 //!
 //!## Feautres
 //!
-//!- `std`  - Enables std related features like accessing current time.
+//!- `alloc` - Enables features that require allocation, like `otpauth` URI handling.
+//!- `std`  - Enables std related features like accessing current time. Implies `alloc`.
+//!- `prf`  - Enables `HOTP::prf` to derive additional values from OTP key.
+//!- `testutil` - Enables insecure helpers for tests and examples, like `secret_from_seed`.
+//!- `debug` - Enables diagnostics helpers, like `TOTP::valid_tokens`. Not intended for production.
+//!- `unicode` - Enables verification of tokens with full-width and Arabic-Indic digits. Otherwise only ASCII digits are accepted.
+//!- `zeroize` - Enables wiping of secret material owned by this crate (HMAC key and retained secret) on drop.
+//!- `serde` - Enables `Serialize`/`Deserialize` of `Algorithm` and `TotpConfig`/`HotpConfig`, as well as `Deserialize` of `TOTP` and `HotpCounter` via them (`Serialize` requires `retain-secret`). Implies `alloc`.
+//!- `retain-secret` - Retains copy of raw secret in `HOTP` for its whole lifetime, in order to export it via `HOTP::to_uri`, `TOTP::to_uri` and `to_config`. Implies `alloc`.
 
 #![warn(missing_docs)]
 
 #![no_std]
-#![cfg_attr(feature = "cargo-clippy", allow(clippy::style))]
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 ///Standard algorithms compatible with `OTP`
 pub enum Algorithm {
     ///SHA-1. Default algorithm.
@@ -19,6 +27,81 @@ pub enum Algorithm {
     SHA512,
 }
 
+impl Algorithm {
+    #[inline]
+    ///Returns length of algorithm's output in bytes.
+    pub const fn digest_len(&self) -> usize {
+        match self {
+            Algorithm::SHA1 => 20,
+            Algorithm::SHA256 => 32,
+            Algorithm::SHA512 => 64,
+        }
+    }
+
+    #[inline]
+    ///Returns length of algorithm's output in bits.
+    pub const fn output_bits(&self) -> u16 {
+        self.digest_len() as u16 * 8
+    }
+
+    #[inline]
+    ///Returns size of algorithm's block in bytes.
+    ///
+    ///HMAC hashes secrets which are longer than block size.
+    pub const fn block_size(&self) -> usize {
+        match self {
+            Algorithm::SHA1 => 64,
+            Algorithm::SHA256 => 64,
+            Algorithm::SHA512 => 128,
+        }
+    }
+
+    #[inline]
+    ///Returns DER encoding (including tag and length) of hash algorithm's object identifier.
+    pub const fn oid(&self) -> &'static [u8] {
+        match self {
+            Algorithm::SHA1 => &[0x06, 0x05, 0x2b, 0x0e, 0x03, 0x02, 0x1a],
+            Algorithm::SHA256 => &[0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01],
+            Algorithm::SHA512 => &[0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x03],
+        }
+    }
+
+    #[inline]
+    ///Returns hash algorithm's object identifier in dotted form.
+    pub const fn oid_str(&self) -> &'static str {
+        match self {
+            Algorithm::SHA1 => "1.3.14.3.2.26",
+            Algorithm::SHA256 => "2.16.840.1.101.3.4.2.1",
+            Algorithm::SHA512 => "2.16.840.1.101.3.4.2.3",
+        }
+    }
+
+    #[inline]
+    ///Returns name of algorithm, as used by `otpauth` URI (e.g. `SHA256`).
+    ///
+    ///Suitable for storing configuration, as it is parsed back by `from_name`.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Algorithm::SHA1 => "SHA1",
+            Algorithm::SHA256 => "SHA256",
+            Algorithm::SHA512 => "SHA512",
+        }
+    }
+
+    ///Parses name of algorithm as returned by `name`, ignoring case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        if name.eq_ignore_ascii_case("SHA1") {
+            Some(Algorithm::SHA1)
+        } else if name.eq_ignore_ascii_case("SHA256") {
+            Some(Algorithm::SHA256)
+        } else if name.eq_ignore_ascii_case("SHA512") {
+            Some(Algorithm::SHA512)
+        } else {
+            None
+        }
+    }
+}
+
 impl Default for Algorithm {
     #[inline(always)]
     fn default() -> Self {
@@ -26,10 +109,165 @@ impl Default for Algorithm {
     }
 }
 
+///Maximum number of digits that can be generated.
+///
+///Dynamic truncation produces 31-bit number, so 10 digits already cover every possible value
+///and anything above would only add leading zeros.
+pub const MAX_DIGITS: u8 = 10;
+
+///Returns maximum code value that can be generated with `digits`.
+///
+///This is `10^digits - 1`, but it never exceeds 31-bit limit of dynamic truncation.
+pub const fn max_code(digits: u8) -> u32 {
+    const TRUNCATION_MAX: u32 = 0x7fff_ffff;
+
+    if digits >= MAX_DIGITS {
+        TRUNCATION_MAX
+    } else {
+        10u32.pow(digits as u32) - 1
+    }
+}
+
+#[cfg(feature = "testutil")]
+///Deterministically expands `seed` into secret of `len` bytes.
+///
+///Bytes after `len` are zero, while `len` itself is capped at 64.
+///
+///**Insecure**: output is fully determined by `seed`, so it must only be used in tests and examples
+///that require stable secret. Never use it to provision real accounts.
+pub fn secret_from_seed(seed: u64, len: usize) -> [u8; 64] {
+    let mut result = [0u8; 64];
+    let len = core::cmp::min(len, result.len());
+
+    for (idx, chunk) in result.chunks_mut(32).enumerate() {
+        let mut input = [0u8; 16];
+        input[..8].copy_from_slice(&seed.to_be_bytes());
+        input[8..].copy_from_slice(&(idx as u64).to_be_bytes());
+        chunk.copy_from_slice(&lhash::sha256(&input));
+    }
+
+    for byte in result[len..].iter_mut() {
+        *byte = 0;
+    }
+
+    result
+}
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
 #[cfg(feature = "std")]
 extern crate std;
 
+mod token;
+mod time;
+pub use time::TimeSource;
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+pub use time::SystemClock;
+mod base32;
+pub use base32::Base32Error;
+mod hex;
+pub use hex::HexError;
+#[cfg(feature = "alloc")]
+mod uri;
+#[cfg(feature = "alloc")]
+pub use uri::{parse_label, ParseUriError};
+#[cfg(feature = "alloc")]
+mod account;
+#[cfg(feature = "alloc")]
+pub use account::Account;
 mod hotp;
-pub use hotp::HOTP;
+pub use hotp::{HOTP, SecretAccumulator, DigitsError, SecretError, MAX_NONCE_LEN};
+mod hotp_counter;
+pub use hotp_counter::HotpCounter;
 mod totp;
-pub use totp::TOTP;
+pub use totp::{TOTP, TotpRef, StrictVerifyResult, VerifyOutcome};
+mod verifier;
+pub use verifier::{TotpVerifier, VerifyResult};
+mod secret_set;
+pub use secret_set::SecretSet;
+mod builder;
+pub use builder::{TotpBuilder, BuildError};
+#[cfg(feature = "serde")]
+mod config;
+#[cfg(feature = "serde")]
+pub use config::{TotpConfig, HotpConfig, ConfigError};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_calculate_max_code() {
+        assert_eq!(max_code(0), 0);
+        assert_eq!(max_code(1), 9);
+        assert_eq!(max_code(6), 999_999);
+        assert_eq!(max_code(8), 99_999_999);
+        assert_eq!(max_code(9), 999_999_999);
+        assert_eq!(max_code(MAX_DIGITS), 0x7fff_ffff);
+        assert_eq!(max_code(11), 0x7fff_ffff);
+        assert_eq!(max_code(u8::MAX), 0x7fff_ffff);
+    }
+
+    #[test]
+    fn should_round_trip_algorithm_name() {
+        for algorithm in [Algorithm::SHA1, Algorithm::SHA256, Algorithm::SHA512].iter() {
+            assert_eq!(Algorithm::from_name(algorithm.name()), Some(*algorithm));
+        }
+
+        assert_eq!(Algorithm::SHA256.name(), "SHA256");
+        assert_eq!(Algorithm::from_name("sha512"), Some(Algorithm::SHA512));
+        assert_eq!(Algorithm::from_name("SHA-256"), None);
+        assert_eq!(Algorithm::from_name(""), None);
+    }
+
+    #[test]
+    fn should_return_digest_len() {
+        assert_eq!(Algorithm::SHA1.digest_len(), 20);
+        assert_eq!(Algorithm::SHA256.digest_len(), 32);
+        assert_eq!(Algorithm::SHA512.digest_len(), 64);
+    }
+
+    #[test]
+    fn should_return_output_bits() {
+        assert_eq!(Algorithm::SHA1.output_bits(), 160);
+        assert_eq!(Algorithm::SHA256.output_bits(), 256);
+        assert_eq!(Algorithm::SHA512.output_bits(), 512);
+    }
+
+    #[test]
+    fn should_return_block_size() {
+        assert_eq!(Algorithm::SHA1.block_size(), 64);
+        assert_eq!(Algorithm::SHA256.block_size(), 64);
+        assert_eq!(Algorithm::SHA512.block_size(), 128);
+    }
+
+    #[test]
+    fn should_return_oid() {
+        assert_eq!(Algorithm::SHA1.oid_str(), "1.3.14.3.2.26");
+        assert_eq!(Algorithm::SHA256.oid_str(), "2.16.840.1.101.3.4.2.1");
+        assert_eq!(Algorithm::SHA512.oid_str(), "2.16.840.1.101.3.4.2.3");
+
+        assert_eq!(Algorithm::SHA1.oid(), [0x06, 0x05, 0x2b, 0x0e, 0x03, 0x02, 0x1a]);
+        assert_eq!(Algorithm::SHA256.oid(), [0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01]);
+        assert_eq!(Algorithm::SHA512.oid(), [0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x03]);
+        for algorithm in [Algorithm::SHA1, Algorithm::SHA256, Algorithm::SHA512].iter() {
+            let oid = algorithm.oid();
+            assert_eq!(oid[1] as usize, oid.len() - 2);
+        }
+    }
+
+    #[cfg(feature = "testutil")]
+    #[test]
+    fn should_generate_secret_from_seed() {
+        let secret = secret_from_seed(1, 20);
+        assert_eq!(secret, secret_from_seed(1, 20));
+        assert_ne!(secret, secret_from_seed(2, 20));
+        assert!(secret[..20].iter().any(|byte| *byte != 0));
+        assert!(secret[20..].iter().all(|byte| *byte == 0));
+        assert_eq!(secret[..20], secret_from_seed(1, 64)[..20]);
+        assert_eq!(secret_from_seed(1, 64), secret_from_seed(1, usize::MAX));
+
+        let totp = TOTP::new(Algorithm::SHA1, &secret[..20]);
+        assert_eq!(totp.generate_num(30, 6), TOTP::new(Algorithm::SHA1, &secret_from_seed(1, 20)[..20]).generate_num(30, 6));
+    }
+}
@@ -0,0 +1,54 @@
+use crate::TOTP;
+
+///Set of `TOTP` for the same account, each with its own secret.
+///
+///Useful during secret rotation, when clients may still use old secret for a grace period.
+///Ordering is preserved, so you can put latest secret first and check which one matched.
+pub struct SecretSet<T> {
+    totps: T,
+}
+
+impl<T: AsRef<[TOTP]>> SecretSet<T> {
+    #[inline]
+    ///Creates new set from collection of `TOTP` (e.g. array, slice or `Vec`).
+    pub const fn new(totps: T) -> Self {
+        Self {
+            totps,
+        }
+    }
+
+    #[inline]
+    ///Returns underlying collection of `TOTP`.
+    pub fn totps(&self) -> &[TOTP] {
+        self.totps.as_ref()
+    }
+
+    ///Checks whether provided `token` corresponds to `time` for any of secrets.
+    ///
+    ///Returns index of the first `TOTP` that matched, or `None` if none matched.
+    pub fn verify(&self, token: &str, time: u64) -> Option<usize> {
+        self.totps.as_ref().iter().position(|totp| totp.verify(token, time))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Algorithm;
+
+    #[test]
+    fn should_verify_with_rotated_secret() {
+        let old = TOTP::new(Algorithm::SHA1, [72, 101, 108, 108, 111, 33, 222, 173, 190, 239]);
+        let new = TOTP::new(Algorithm::SHA1, b"12345678901234567890");
+
+        let mut new_token = [0u8; 6];
+        new.generate_to(1606206826, &mut new_token[..]);
+        let new_token = core::str::from_utf8(&new_token).expect("UTF-8 compatible output");
+        assert!(!old.verify(new_token, 1606206826));
+
+        let set = SecretSet::new([new, old]);
+        assert_eq!(set.verify(new_token, 1606206826), Some(0));
+        assert_eq!(set.verify("458443", 1606206826), Some(1));
+        assert_eq!(set.verify("458443", 30), None);
+    }
+}
@@ -0,0 +1,50 @@
+///Source of current time.
+///
+///Allows to use `*_with` methods of `TOTP` on platforms without `std` (e.g. with RTC) or where
+///`std` time is not available, like `wasm32-unknown-unknown` in browser:
+///
+///```ignore
+///struct BrowserClock;
+///
+///impl otpshka::TimeSource for BrowserClock {
+///    fn now_secs(&self) -> u64 {
+///        //`Date.now()` returns milliseconds since UNIX epoch
+///        (js_sys::Date::now() / 1000.0) as u64
+///    }
+///}
+///
+///let is_valid = totp.verify_with(&BrowserClock, token);
+///```
+pub trait TimeSource {
+    ///Returns number of seconds since UNIX epoch.
+    fn now_secs(&self) -> u64;
+}
+
+impl<T: TimeSource + ?Sized> TimeSource for &T {
+    #[inline(always)]
+    fn now_secs(&self) -> u64 {
+        T::now_secs(self)
+    }
+}
+
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+#[derive(Debug, Default, Clone, Copy)]
+///System clock from `std`, used by `*_now` methods.
+///
+///Time before UNIX epoch is reported as `0`.
+///
+///It is not available on `wasm32-unknown-unknown`, where `std` has no time, together with `*_now` methods.
+///Use `*_with` methods with your own `TimeSource` instead.
+pub struct SystemClock;
+
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+impl TimeSource for SystemClock {
+    fn now_secs(&self) -> u64 {
+        use std::time::{SystemTime, UNIX_EPOCH};
+        match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(now) => now.as_secs(),
+            //Time before UNIX epoch is treated as epoch
+            Err(_) => 0,
+        }
+    }
+}
@@ -0,0 +1,226 @@
+use crate::MAX_DIGITS;
+
+#[inline]
+fn digit_value(ch: char) -> Option<u32> {
+    match ch {
+        '0'..='9' => Some(ch as u32 - '0' as u32),
+        //Full-width digits
+        #[cfg(feature = "unicode")]
+        '\u{FF10}'..='\u{FF19}' => Some(ch as u32 - 0xFF10),
+        //Arabic-Indic digits
+        #[cfg(feature = "unicode")]
+        '\u{0660}'..='\u{0669}' => Some(ch as u32 - 0x0660),
+        //Extended Arabic-Indic digits
+        #[cfg(feature = "unicode")]
+        '\u{06F0}'..='\u{06F9}' => Some(ch as u32 - 0x06F0),
+        _ => None,
+    }
+}
+
+///Token, normalized to ASCII digits.
+pub(crate) struct Token {
+    buffer: [u8; MAX_DIGITS as usize],
+    len: u8,
+}
+
+impl Token {
+    #[inline(always)]
+    ///Returns number of digits in token.
+    pub(crate) const fn digits(&self) -> u8 {
+        self.len
+    }
+
+    #[inline(always)]
+    ///Returns ASCII digits of token.
+    pub(crate) fn as_bytes(&self) -> &[u8] {
+        &self.buffer[..self.len as usize]
+    }
+
+    ///Checks whether token matches `code`, padded with zeros to the same number of digits.
+    ///
+    ///Comparison is performed over every digit without early return, so that time to reject
+    ///does not depend on position of the first mismatching digit.
+    pub(crate) fn matches(&self, mut code: u32) -> bool {
+        let mut expected = [0u8; MAX_DIGITS as usize];
+        let expected = &mut expected[..self.len as usize];
+        for digit in expected.iter_mut().rev() {
+            *digit = b'0' + (code % 10) as u8;
+            code /= 10;
+        }
+
+        let mut diff = (code != 0) as u8;
+        for (expected, actual) in expected.iter().zip(self.as_bytes()) {
+            diff |= expected ^ actual;
+        }
+        diff == 0
+    }
+}
+
+///Parses `token` into ASCII digits.
+///
+///Only ASCII digits are accepted, unless `unicode` feature is enabled, in which case full-width
+///and Arabic-Indic digits are accepted as well.
+///
+///Returns `None` if `token` is empty, contains anything but digits or has more than `MAX_DIGITS` digits.
+pub(crate) fn parse_token(token: &str) -> Option<Token> {
+    let mut result = Token {
+        buffer: [0; MAX_DIGITS as usize],
+        len: 0,
+    };
+
+    for ch in token.chars() {
+        let digit = digit_value(ch)?;
+        if result.len == MAX_DIGITS {
+            return None;
+        }
+
+        result.buffer[result.len as usize] = b'0' + digit as u8;
+        result.len += 1;
+    }
+
+    match result.len {
+        0 => None,
+        _ => Some(result),
+    }
+}
+
+///Parses `token` of ASCII digits.
+///
+///Unlike `parse_token`, only ASCII digits are accepted regardless of `unicode` feature.
+///
+///Returns `None` if `token` is empty, contains anything but ASCII digits or has more than `MAX_DIGITS` digits.
+pub(crate) fn parse_token_bytes(token: &[u8]) -> Option<Token> {
+    if token.is_empty() || token.len() > MAX_DIGITS as usize || !token.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+
+    let mut result = Token {
+        buffer: [0; MAX_DIGITS as usize],
+        len: token.len() as u8,
+    };
+    result.buffer[..token.len()].copy_from_slice(token);
+    Some(result)
+}
+
+///Checks whether `token` consists of the same digit repeated or of ascending/descending sequence (e.g. `123456`).
+///
+///Tokens with less than 2 digits or containing anything but digits are never considered weak.
+pub(crate) fn is_weak_token(token: &str) -> bool {
+    let mut digits = token.chars().map(digit_value);
+    let mut prev = match digits.next() {
+        Some(Some(digit)) => digit as i32,
+        _ => return false,
+    };
+
+    let mut step = None;
+    for digit in digits {
+        let digit = match digit {
+            Some(digit) => digit as i32,
+            None => return false,
+        };
+
+        let diff = digit - prev;
+        if diff.abs() > 1 {
+            return false;
+        }
+
+        match step {
+            None => step = Some(diff),
+            Some(step) if step != diff => return false,
+            Some(_) => (),
+        }
+        prev = digit;
+    }
+
+    step.is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_token(token: &str, expected: &[u8]) {
+        let token = parse_token(token).expect("to parse");
+        assert_eq!(token.as_bytes(), expected);
+        assert_eq!(token.digits() as usize, expected.len());
+    }
+
+    #[test]
+    fn should_parse_ascii_token() {
+        assert_token("082772", b"082772");
+        assert_token("0", b"0");
+        assert_token("999999999", b"999999999");
+        assert_token("0000000000", b"0000000000");
+        assert_token("4294967296", b"4294967296");
+    }
+
+    #[test]
+    fn should_reject_invalid_token() {
+        assert!(parse_token("").is_none());
+        assert!(parse_token("+82772").is_none());
+        assert!(parse_token("-82772").is_none());
+        assert!(parse_token(" 082772").is_none());
+        assert!(parse_token("08a772").is_none());
+        assert!(parse_token("00000000000").is_none());
+        assert!(parse_token("42949672960").is_none());
+    }
+
+    #[test]
+    fn should_parse_byte_token() {
+        assert_eq!(parse_token_bytes(b"082772").expect("to parse").as_bytes(), b"082772");
+        assert_eq!(parse_token_bytes(b"0000000000").expect("to parse").digits(), 10);
+
+        assert!(parse_token_bytes(b"").is_none());
+        assert!(parse_token_bytes(b"08a772").is_none());
+        assert!(parse_token_bytes(b"082772\n").is_none());
+        assert!(parse_token_bytes(b"00000000000").is_none());
+        assert!(parse_token_bytes(&[0xFF, 0x10]).is_none());
+        assert!(parse_token_bytes("０８２７７２".as_bytes()).is_none());
+    }
+
+    #[test]
+    fn should_match_code_padded_to_token_length() {
+        let token = parse_token("082772").expect("to parse");
+        assert!(token.matches(82772));
+        assert!(!token.matches(82773));
+        assert!(!token.matches(182772));
+        assert!(!token.matches(0));
+
+        assert!(parse_token("0082772").expect("to parse").matches(82772));
+        assert!(!parse_token("82772").expect("to parse").matches(182772));
+        assert!(parse_token("000000").expect("to parse").matches(0));
+    }
+
+    #[test]
+    fn should_detect_weak_token() {
+        assert!(is_weak_token("000000"));
+        assert!(is_weak_token("999999"));
+        assert!(is_weak_token("123456"));
+        assert!(is_weak_token("876543"));
+        assert!(is_weak_token("01"));
+
+        assert!(!is_weak_token(""));
+        assert!(!is_weak_token("0"));
+        assert!(!is_weak_token("082772"));
+        assert!(!is_weak_token("123455"));
+        assert!(!is_weak_token("135791"));
+        assert!(!is_weak_token("111111a"));
+        assert!(!is_weak_token("890123"));
+    }
+
+    #[cfg(feature = "unicode")]
+    #[test]
+    fn should_parse_unicode_token() {
+        assert_token("０８２７７２", b"082772");
+        assert_token("٠٨٢٧٧٢", b"082772");
+        assert_token("۰۸۲۷۷۲", b"082772");
+        assert_token("０8٢۷７2", b"082772");
+    }
+
+    #[cfg(not(feature = "unicode"))]
+    #[test]
+    fn should_reject_unicode_token() {
+        assert!(parse_token("０８２７７２").is_none());
+        assert!(parse_token("٠٨٢٧٧٢").is_none());
+    }
+}
@@ -1,19 +1,69 @@
-use crate::hotp::HOTP;
+use core::borrow::Borrow;
+
+use crate::hotp::{HOTP, DigitsError};
+use crate::builder::TotpBuilder;
+use crate::token::{parse_token, parse_token_bytes, is_weak_token, Token};
+
+use crate::time::TimeSource;
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+use crate::time::SystemClock;
 
 use super::Algorithm;
+#[cfg(any(test, feature = "debug"))]
+use crate::MAX_DIGITS;
+
+#[inline]
+const fn offset_counter(counter: u64, window_offset: i64) -> u64 {
+    if window_offset < 0 {
+        counter.saturating_sub(window_offset.unsigned_abs())
+    } else {
+        counter.saturating_add(window_offset as u64)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///Result of `TOTP::verify_strict_entropy`
+pub struct StrictVerifyResult {
+    ///Whether token is valid, same as `TOTP::verify`
+    pub matched: bool,
+    ///Whether valid token looks too regular (e.g. `000000` or `123456`).
+    ///
+    ///It is only set when `matched` is `true`.
+    pub weak: bool,
+}
 
-#[cfg(feature = "std")]
-fn current_time_s() -> u64 {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    SystemTime::now().duration_since(UNIX_EPOCH)
-                     .expect("now should be after epoch")
-                     .as_secs()
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///Result of `TOTP::verify_detailed`
+pub enum VerifyOutcome {
+    ///Token contains anything but digits or its length differs from configured number of digits.
+    Malformed,
+    ///Token is well-formed, but does not match any window within skew.
+    Mismatch,
+    ///Token matches window at `offset` relative to window containing time.
+    ///
+    ///Offset is the same as returned by `TOTP::verify_skew`.
+    Match {
+        ///Offset of matched window. Negative for past windows and positive for future ones.
+        offset: i64,
+    },
+}
+
+impl VerifyOutcome {
+    #[inline]
+    ///Returns whether token matched.
+    pub const fn is_match(&self) -> bool {
+        matches!(self, VerifyOutcome::Match { .. })
+    }
 }
 
 ///Modification of `Htop` algorithm that uses unix timestamp within `window`
-pub struct TOTP {
+///
+///By default it owns its `HOTP`, but it can be constructed with anything that borrows `HOTP`
+///(e.g. reference or `Arc`) via `from_hotp`, allowing many instances to share single key
+///without deriving it again.
+pub struct TOTP<K = HOTP> {
     ///Basic HMAC OTP algorithm, which is used as corner-stone of TOTP.
-    inner: HOTP,
+    inner: K,
     ///Number of seconds allowed as network delay.
     ///
     ///Default and recommended is 1.
@@ -22,27 +72,140 @@ pub struct TOTP {
     ///
     ///Default and recommended is 30.
     pub window: u64,
+    ///Unix timestamp in seconds from which windows are counted (`T0` in RFC 6238).
+    ///
+    ///Counter is `(time - t0) / window`, while time before `t0` is treated as `t0`.
+    ///Hence `skew` never reaches windows before `t0`.
+    ///Default is 0.
+    pub t0: u64,
+    ///Number of digits in generated password.
+    pub(crate) digits: u8,
 }
 
-impl TOTP {
+impl TOTP<HOTP> {
     #[inline]
     ///Initializes algorithm using provided `algorithm` and `secret`
     ///
     ///- `algorithm` - Generally acceptable are HMAC based on `sha-1`, `sha-256` and `sha-512`
     ///- `secret` - Raw bytes used to derive HMAC key. User is responsible to decode it before
-    ///passing.
+    ///  passing.
+    ///
+    ///Configuration is default: `skew` is 1, `window` is 30 and `digits` is 6.
+    ///Use `builder` to set validated custom configuration instead of mutating fields.
     pub fn new<T: AsRef<[u8]>>(algorithm: Algorithm, secret: T) -> Self {
+        Self::from_hotp(HOTP::new(algorithm, secret))
+    }
+
+    #[inline(always)]
+    ///Starts building algorithm with custom configuration using provided `algorithm` and `secret`
+    pub fn builder<T: AsRef<[u8]>>(algorithm: Algorithm, secret: T) -> TotpBuilder<T> {
+        TotpBuilder::new(algorithm, secret)
+    }
+
+    #[inline]
+    ///Initializes algorithm using provided `algorithm` and base32 `encoded` secret with default configuration.
+    ///
+    ///Refer to `HOTP::from_base32` for details of decoding.
+    pub fn from_base32<T: AsRef<[u8]>>(algorithm: Algorithm, encoded: T) -> Result<Self, crate::Base32Error> {
+        HOTP::from_base32(algorithm, encoded).map(Self::from_hotp)
+    }
+
+    #[inline]
+    ///Initializes algorithm using provided `algorithm` and hex `encoded` secret with default configuration.
+    ///
+    ///Refer to `HOTP::from_hex` for details of decoding.
+    pub fn from_hex<T: AsRef<[u8]>>(algorithm: Algorithm, encoded: T) -> Result<Self, crate::HexError> {
+        HOTP::from_hex(algorithm, encoded).map(Self::from_hotp)
+    }
+
+    #[cfg(feature = "alloc")]
+    ///Parses `otpauth://totp/` URI, returning `TOTP` with number of digits.
+    ///
+    ///`secret` is required, while `algorithm`, `digits` and `period` fall back to SHA-1, 6 and 30.
+    ///`window` is set to `period`.
+    pub fn from_uri(uri: &str) -> Result<(TOTP, u8), crate::ParseUriError> {
+        let account = crate::Account::from_uri(uri)?;
+        Ok((account.totp(), account.digits))
+    }
+}
+
+impl<K: Borrow<HOTP>> core::fmt::Debug for TOTP<K> {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        fmt.debug_struct("TOTP")
+           .field("algorithm", &self.algorithm())
+           .field("window", &self.window)
+           .field("skew", &self.skew)
+           .field("t0", &self.t0)
+           .field("digits", &self.digits)
+           .field("key", &format_args!("<redacted>"))
+           .finish()
+    }
+}
+
+///`TOTP` that borrows its key.
+pub type TotpRef<'k> = TOTP<&'k HOTP>;
+
+impl<K: Borrow<HOTP>> TOTP<K> {
+    #[inline]
+    ///Initializes algorithm using already created `HOTP` with default configuration.
+    ///
+    ///`inner` can be anything that borrows `HOTP`, hence you can share single key via reference
+    ///or `Arc`.
+    pub fn from_hotp(inner: K) -> Self {
         Self {
-            inner: HOTP::new(algorithm, secret),
+            inner,
             skew: 1,
             window: 30,
+            t0: 0,
+            digits: 6,
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn hotp(&self) -> &HOTP {
+        self.inner.borrow()
+    }
+
+    #[inline(always)]
+    ///Returns number of digits configured for this algorithm.
+    ///
+    ///Default is 6.
+    pub const fn digits(&self) -> u8 {
+        self.digits
+    }
+
+    ///Sets number of digits used by methods that rely on configuration (e.g. `verify_exact`).
+    ///
+    ///Must be within `1..=MAX_DIGITS`, otherwise configuration is left unchanged.
+    pub fn set_digits(&mut self, digits: u8) -> Result<(), DigitsError> {
+        match digits {
+            0 => Err(DigitsError::Zero),
+            digits if digits > crate::MAX_DIGITS => Err(DigitsError::TooMany),
+            digits => {
+                self.digits = digits;
+                Ok(())
+            }
         }
     }
 
+    #[inline(always)]
+    ///Returns HMAC algorithm.
+    pub fn algorithm(&self) -> Algorithm {
+        self.hotp().algorithm()
+    }
+
+    #[inline(always)]
+    ///Returns fingerprint of the secret.
+    ///
+    ///Refer to `HOTP::key_fingerprint` for details.
+    pub fn key_fingerprint(&self) -> [u8; 8] {
+        self.hotp().key_fingerprint()
+    }
+
     #[inline(always)]
     ///Signs provided `time` value using stored HMAC key.
-    pub fn sign(&self, time: u64) -> impl AsRef<[u8]> + Clone + Copy {
-        self.inner.sign(time / self.window)
+    pub fn sign(&self, time: u64) -> impl AsRef<[u8]> + Copy {
+        self.hotp().sign(self.counter(time))
     }
 
     #[inline(always)]
@@ -50,113 +213,1477 @@ impl TOTP {
     ///
     ///Note that in this case you must handle missing padding yourself.
     pub fn generate_num(&self, time: u64, digits: u8) -> u32 {
-        self.inner.generate_num(time / self.window, digits)
+        self.hotp().generate_num(self.counter(time), digits)
+    }
+
+    #[cfg(feature = "alloc")]
+    #[inline(always)]
+    ///Generates password for provided `time` with length of `digits`, padded with zeros.
+    pub fn generate(&self, time: u64, digits: u8) -> alloc::string::String {
+        self.hotp().generate(self.counter(time), digits)
+    }
+
+    #[inline]
+    ///Generates password as number for window that is `window_offset` windows away from the one containing `time`.
+    ///
+    ///Negative `window_offset` refers to past windows, while zero is the same as `generate_num`.
+    ///Resulting window is saturated at the first window (i.e. it never goes below zero).
+    pub fn generate_offset(&self, time: u64, window_offset: i64, digits: u8) -> u32 {
+        self.hotp().generate_num(offset_counter(self.counter(time), window_offset), digits)
     }
 
     #[inline(always)]
     ///Generates pass based on provided `time` and writes it into provided `dest`.
     ///
-    ///This always writes `dest.as_ref().len()`.
+    ///This always writes `dest.as_ref().len()`, which is number of digits and must be within `1..=MAX_DIGITS`.
     ///
-    ///Recommended buffer length is be within `6..8`
+    ///## Panics
+    ///
+    ///In debug builds, if length of `dest` is outside of `1..=MAX_DIGITS`
     pub fn generate_to<T: AsMut<[u8]>>(&self, time: u64, dest: T) {
-        self.inner.generate_to(time / self.window, dest)
+        self.hotp().generate_to(self.counter(time), dest)
     }
 
-    #[cfg(feature = "std")]
+    #[inline(always)]
+    ///Generates password for provided `time` as array of `N` ASCII digits.
+    ///
+    ///Number of digits is `N`, which must be within `1..=MAX_DIGITS`.
+    ///
+    ///## Panics
+    ///
+    ///In debug builds, if `N` is outside of `1..=MAX_DIGITS`
+    pub fn generate_to_array<const N: usize>(&self, time: u64) -> [u8; N] {
+        self.hotp().generate_to_array(self.counter(time))
+    }
+
+    ///Generates passwords for the last `N` windows up to the one containing `time`.
+    ///
+    ///Each password is written as ASCII into the first `digits` bytes of its entry, with remaining bytes being zero.
+    ///Entries are ordered from oldest to newest, so the last one corresponds to `time`.
+    ///Entries for windows before `t0` are left zeroed.
+    ///
+    ///`digits` must be within `1..=8`.
+    ///
+    ///## Panics
+    ///
+    ///If `digits` is outside of `1..=8`
+    pub fn fill_recent<const N: usize>(&self, time: u64, digits: u8) -> [[u8; 8]; N] {
+        assert!(digits > 0 && digits <= 8, "digits must be within 1..=8");
+
+        let counter = self.counter(time);
+        let mut result = [[0u8; 8]; N];
+        for (idx, entry) in result.iter_mut().rev().enumerate() {
+            match counter.checked_sub(idx as u64) {
+                Some(counter) => self.hotp().generate_to(counter, &mut entry[..digits as usize]),
+                None => break,
+            }
+        }
+
+        result
+    }
+
+    ///Returns number of digit positions that differ between password of window containing `time` and the previous one.
+    ///
+    ///Useful to animate only changed digits when password changes.
+    ///As there is no window before first one, all `digits` are considered changed for it.
+    pub fn code_changed_digits(&self, time: u64, digits: u8) -> u8 {
+        let counter = self.counter(time);
+        let prev_counter = match counter.checked_sub(1) {
+            Some(prev_counter) => prev_counter,
+            None => return digits,
+        };
+
+        let mut code = self.hotp().generate_num(counter, digits);
+        let mut prev_code = self.hotp().generate_num(prev_counter, digits);
+        let mut changed = 0;
+        for _ in 0..digits {
+            if code % 10 != prev_code % 10 {
+                changed += 1;
+            }
+            code /= 10;
+            prev_code /= 10;
+        }
+
+        changed
+    }
+
+    #[inline(always)]
+    ///Returns number of seconds elapsed since `t0`.
+    const fn elapsed(&self, time: u64) -> u64 {
+        time.saturating_sub(self.t0)
+    }
+
+    #[inline(always)]
+    ///Returns counter of window containing `time`.
+    const fn counter(&self, time: u64) -> u64 {
+        self.elapsed(time) / self.window
+    }
+
+    #[inline]
+    ///Returns counter of window containing `time`.
+    ///
+    ///Returns `0` when `window` is zero.
+    pub const fn current_counter(&self, time: u64) -> u64 {
+        match self.elapsed(time).checked_div(self.window) {
+            Some(counter) => counter,
+            None => 0,
+        }
+    }
+
+    #[inline]
+    ///Returns number of seconds remaining until window containing `time` expires.
+    ///
+    ///It is within `1..=window`, so exactly at window boundary it is `window`.
+    ///Returns `0` when `window` is zero.
+    pub const fn ttl(&self, time: u64) -> u64 {
+        match self.elapsed(time).checked_rem(self.window) {
+            Some(elapsed) => self.window - elapsed,
+            None => 0,
+        }
+    }
+
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    #[inline(always)]
+    ///Returns number of seconds remaining until current window expires, using system time.
+    ///
+    ///Refer to `ttl` for details.
+    pub fn ttl_now(&self) -> u64 {
+        self.ttl_with(&SystemClock)
+    }
+
+    #[inline(always)]
+    ///Returns number of seconds remaining until current window expires, using time of provided `clock`.
+    ///
+    ///Refer to `ttl` for details.
+    pub fn ttl_with<C: TimeSource>(&self, clock: &C) -> u64 {
+        self.ttl(clock.now_secs())
+    }
+
+    #[cfg(feature = "alloc")]
+    ///Generates password of `digits` for `time`, unless it expires in less than `min_remaining_secs`.
+    ///
+    ///In later case password of the next window is generated instead, so user is not given password
+    ///that becomes invalid right away. UI should indicate that it is upcoming password.
+    ///Note that next password is rejected by verifier until its window starts, unless it is within `skew`.
+    pub fn generate_safe(&self, time: u64, digits: u8, min_remaining_secs: u64) -> alloc::string::String {
+        let window_offset = if self.ttl(time) < min_remaining_secs { 1 } else { 0 };
+        self.hotp().generate(offset_counter(self.counter(time), window_offset), digits)
+    }
+
+    #[cfg(feature = "retain-secret")]
+    ///Creates `otpauth://totp/` URI, which can be used to provision account (e.g. via QR code).
+    ///
+    ///- `label` - Name of account (e.g. user name or email).
+    ///- `issuer` - Optional name of service, which is prepended to label and added as parameter.
+    ///- `digits` - Number of digits in password.
+    ///
+    ///Secret is encoded as base32 without padding, while label and issuer are percent-encoded.
+    ///`period` is set to `window`.
+    pub fn to_uri(&self, label: &str, issuer: Option<&str>, digits: u8) -> alloc::string::String {
+        use core::fmt::Write;
+
+        let mut uri = crate::uri::start_uri("totp", issuer, label, self.hotp().secret(), self.algorithm(), digits);
+        let _ = write!(uri, "&period={}", self.window);
+        uri
+    }
+
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    #[inline(always)]
+    ///Generates password of `digits` using system time, unless it expires in less than `min_remaining_secs`.
+    ///
+    ///Refer to `generate_safe` for details.
+    pub fn generate_now_safe(&self, digits: u8, min_remaining_secs: u64) -> alloc::string::String {
+        self.generate_safe_with(&SystemClock, digits, min_remaining_secs)
+    }
+
+    #[cfg(feature = "alloc")]
+    #[inline(always)]
+    ///Generates password of `digits` using time of provided `clock`, unless it expires in less than `min_remaining_secs`.
+    ///
+    ///Refer to `generate_safe` for details.
+    pub fn generate_safe_with<C: TimeSource>(&self, clock: &C, digits: u8, min_remaining_secs: u64) -> alloc::string::String {
+        self.generate_safe(clock.now_secs(), digits, min_remaining_secs)
+    }
+
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    #[inline(always)]
+    ///Returns number of digit positions that differ between current password and the previous one, using system time.
+    ///
+    ///Refer to `code_changed_digits` for details.
+    pub fn code_changed_digits_now(&self, digits: u8) -> u8 {
+        self.code_changed_digits_with(&SystemClock, digits)
+    }
+
+    #[inline(always)]
+    ///Returns number of digit positions that differ between current password and the previous one, using time of provided `clock`.
+    ///
+    ///Refer to `code_changed_digits` for details.
+    pub fn code_changed_digits_with<C: TimeSource>(&self, clock: &C, digits: u8) -> u8 {
+        self.code_changed_digits(clock.now_secs(), digits)
+    }
+
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    #[inline(always)]
+    ///Generates password as number for window that is `window_offset` windows away from current one.
+    ///
+    ///Current system time is sampled once, so calling it for several offsets is consistent only within the same window.
+    ///Offset `0` is the current code. Refer to `generate_offset` for details.
+    pub fn code_at_window_offset_now(&self, window_offset: i64, digits: u8) -> u32 {
+        self.code_at_window_offset_with(&SystemClock, window_offset, digits)
+    }
+
+    #[inline(always)]
+    ///Generates password as number for window that is `window_offset` windows away from window at time of provided `clock`.
+    ///
+    ///Time is sampled once. Refer to `generate_offset` for details.
+    pub fn code_at_window_offset_with<C: TimeSource>(&self, clock: &C, window_offset: i64, digits: u8) -> u32 {
+        self.generate_offset(clock.now_secs(), window_offset, digits)
+    }
+
+    #[inline(always)]
+    ///Generates pass using time of provided `clock`
+    pub fn generate_to_with<C: TimeSource, T: AsMut<[u8]>>(&self, clock: &C, dest: T) {
+        self.generate_to(clock.now_secs(), dest)
+    }
+
+    #[cfg(feature = "alloc")]
+    #[inline]
+    ///Generates password with configured number of digits using time of provided `clock`.
+    pub fn generate_current_with<C: TimeSource>(&self, clock: &C) -> alloc::string::String {
+        self.generate(clock.now_secs(), self.digits)
+    }
+
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    #[inline(always)]
+    ///Generates password with configured number of digits using current system time.
+    pub fn generate_current(&self) -> alloc::string::String {
+        self.generate_current_with(&SystemClock)
+    }
+
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
     #[inline(always)]
     ///Generates pass using current system time from `std`
     pub fn generate_to_now<T: AsMut<[u8]>>(&self, dest: T) {
-        self.generate_to(current_time_s(), dest)
+        self.generate_to_with(&SystemClock, dest)
     }
 
     #[inline]
-    ///Checks whether provided `token` corresponds to `time`.
-    pub fn verify(&self, token: &str, time: u64) -> bool {
-        debug_assert!(token.len() <= u8::max_value() as _);
+    ///Looks up window that matches provided `token` at `time` within `skew`, returning its counter.
+    fn find_window(&self, token: &str, time: u64, skew: u8) -> Option<u64> {
+        let token = parse_token(token)?;
+        self.find_window_parsed_with_skew(&token, time, skew)
+    }
 
-        let expected = match u32::from_str_radix(token, 10) {
-            Ok(expected) => expected,
-            Err(_) => return false,
-        };
+    #[inline]
+    ///Looks up window that matches already parsed token at `time`, returning its counter.
+    pub(crate) fn find_window_parsed(&self, token: &Token, time: u64) -> Option<u64> {
+        self.find_window_parsed_with_skew(token, time, self.skew)
+    }
+
+    fn find_window_parsed_with_skew(&self, token: &Token, time: u64, skew: u8) -> Option<u64> {
+        let digits = token.digits();
+        let hotp = self.hotp();
+        self.find_window_by(time, skew, |counter| token.matches(hotp.generate_num(counter, digits)))
+    }
 
-        if self.inner.generate_num(time / self.window, token.len() as u8) == expected {
-            return true;
+    ///Looks up window at `time` within `skew` for which `matches` returns `true`, returning its counter.
+    fn find_window_by<F: Fn(u64) -> bool>(&self, time: u64, skew: u8, matches: F) -> Option<u64> {
+        let time = self.elapsed(time);
+        let counter = time / self.window;
+        if matches(counter) {
+            return Some(counter);
         }
 
-        for time_offset in 1..=self.skew as u64 {
-            if self.inner.generate_num((time + time_offset) / self.window, token.len() as u8) == expected {
-                return true;
+        //Offsets within the same window produce the same counter, so only check each window once.
+        let mut next_counter = counter;
+        let mut prev_counter = counter;
+        for time_offset in 1..=skew as u64 {
+            let counter = time.saturating_add(time_offset) / self.window;
+            if counter != next_counter {
+                next_counter = counter;
+                if matches(counter) {
+                    return Some(counter);
+                }
             }
 
-            if self.inner.generate_num((time - time_offset) / self.window, token.len() as u8) == expected {
-                return true;
+            //There is no window before `t0`
+            if let Some(time) = time.checked_sub(time_offset) {
+                let counter = time / self.window;
+                if counter != prev_counter {
+                    prev_counter = counter;
+                    if matches(counter) {
+                        return Some(counter);
+                    }
+                }
             }
         }
 
-        false
+        None
+    }
+
+    #[inline(always)]
+    ///Generates Steam Guard code for provided `time`.
+    ///
+    ///Refer to `HOTP::generate_steam` for details.
+    pub fn generate_steam(&self, time: u64) -> [u8; 5] {
+        self.hotp().generate_steam(self.counter(time))
+    }
+
+    ///Checks whether provided Steam Guard `token` corresponds to `time`, allowing `skew` same as `verify`.
+    ///
+    ///Letters are compared ignoring case.
+    pub fn verify_steam(&self, token: &str, time: u64) -> bool {
+        self.find_window_by(time, self.skew, |counter| crate::hotp::steam_matches(token.as_bytes(), &self.hotp().generate_steam(counter))).is_some()
+    }
+
+    ///Checks whether provided `token` has exactly `expected_digits` and corresponds to `time`.
+    ///
+    ///Unlike `verify`, number of digits is not derived from `token`, so shorter token cannot be
+    ///accepted when longer is expected. Token of mismatching length is rejected without computing HMAC.
+    pub fn verify_digits(&self, token: &str, time: u64, expected_digits: u8) -> bool {
+        match parse_token(token) {
+            Some(token) if token.digits() == expected_digits => self.find_window_parsed(&token, time).is_some(),
+            _ => false,
+        }
     }
 
-    #[cfg(feature = "std")]
     #[inline]
-    ///Checks whether provided `token` corresponds to current system time.
-    pub fn verify_now(&self, token: &str) -> bool {
-        self.verify(token, current_time_s())
+    ///Checks whether provided `token` has exactly configured number of digits and corresponds to `time`.
+    ///
+    ///Same as `verify_digits` with `digits()`, so leading zeros cannot be omitted.
+    pub fn verify_exact(&self, token: &str, time: u64) -> bool {
+        self.verify_digits(token, time, self.digits)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    ///Returns maximum number of HMAC operations performed by single `verify` call.
+    ///
+    ///Each distinct window within `skew` seconds around time is checked only once, and `2 * skew + 1`
+    ///seconds can span at most `(2 * skew - 1) / window + 2` windows (e.g. window boundary can be on both sides
+    ///of time only when `skew` exceeds half of `window`). Can be used to weight verification attempts in rate limiting.
+    pub const fn verify_cost(&self) -> u32 {
+        match self.skew as u64 {
+            0 => 1,
+            skew => ((2 * skew - 1) / self.window + 2) as u32,
+        }
+    }
 
-    #[test]
-    fn should_test_totp_window() {
-        let input = [
-            (30, "996554"),
-            (60, "602287"),
-        ];
+    ///Checks provided `token` against every window within `skew` around `time`, returning bitmask of matches.
+    ///
+    ///Bit `15 + offset` is set when window at `offset` matched, hence bit 15 corresponds to window
+    ///containing `time`, lower bits to past windows and higher bits to future windows.
+    ///
+    ///Usually at most single bit is set. Only windows up to 15 offsets in either direction are checked,
+    ///so matches beyond that are not reported even if `skew` allows them.
+    pub fn verify_mask(&self, token: &str, time: u64) -> u32 {
+        const MAX_OFFSET: u64 = 15;
 
-        let secret = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
-        let totp = TOTP::new(Default::default(), secret);
+        let token = match parse_token(token) {
+            Some(token) => token,
+            None => return 0,
+        };
+        let digits = token.digits();
 
-        for (time, expected) in input.iter() {
-            let mut output = [0u8, 0, 0, 0, 0, 0];
-            assert_eq!(output.len(), expected.len());
+        let skew = self.skew as u64;
+        let time = self.elapsed(time);
+        let counter = time / self.window;
+        let from = core::cmp::max(time.saturating_sub(skew) / self.window, counter.saturating_sub(MAX_OFFSET));
+        let to = core::cmp::min(time.saturating_add(skew) / self.window, counter.saturating_add(MAX_OFFSET));
 
-            totp.generate_to(*time, &mut output[..]);
-            let token = core::str::from_utf8(&output).expect("UTF-8 compatible output");
-            assert_eq!(token, *expected);
-            assert!(totp.verify(token, *time));
-            assert!(totp.verify(token, *time + 30));
-            assert!(totp.verify(token, *time - 1));
+        let mut mask = 0;
+        for window in from..=to {
+            if token.matches(self.hotp().generate_num(window, digits)) {
+                mask |= 1 << (window + MAX_OFFSET - counter);
+            }
         }
+
+        mask
     }
 
-    #[test]
-    fn should_test_totp() {
-        let input = [
-            (1606206826, "458443"),
-            (1606206917, "779542"),
-            (1606206950, "082772"),
-        ];
+    #[inline]
+    ///Checks whether provided `token` corresponds to `time`.
+    ///
+    ///Number of digits is derived from `token`, hence token without leading zeros (e.g. `82772`
+    ///instead of `082772`) is accepted as well. Use `verify_exact` to reject it.
+    pub fn verify(&self, token: &str, time: u64) -> bool {
+        self.find_window(token, time, self.skew).is_some()
+    }
 
-        let secret = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
-        let totp = TOTP::new(Default::default(), secret);
+    ///Checks whether provided `token` corresponds to `time`, distinguishing malformed token from mismatch.
+    ///
+    ///Unlike `verify`, token must have exactly configured number of digits (refer to `digits()`),
+    ///otherwise it is considered malformed.
+    ///Malformed input may indicate attack or bug of the client, while mismatch is usually typo.
+    ///On match offset of window allows to detect clock drift, same as `verify_skew`.
+    pub fn verify_detailed(&self, token: &str, time: u64) -> VerifyOutcome {
+        let token = match parse_token(token) {
+            Some(token) if token.digits() == self.digits => token,
+            _ => return VerifyOutcome::Malformed,
+        };
 
-        for (time, expected) in input.iter() {
-            let mut output = [0u8, 0, 0, 0, 0, 0];
-            assert_eq!(output.len(), expected.len());
+        match self.find_window_parsed(&token, time) {
+            Some(matched) => VerifyOutcome::Match {
+                offset: matched as i64 - self.counter(time) as i64,
+            },
+            None => VerifyOutcome::Mismatch,
+        }
+    }
 
-            totp.generate_to(*time, &mut output[..]);
-            let token = core::str::from_utf8(&output).expect("UTF-8 compatible output");
-            assert_eq!(token, *expected);
-            assert!(totp.verify(token, *time));
-            assert!(totp.verify(token, *time + 10));
-            assert!(totp.verify(token, *time - 10));
+    #[inline]
+    ///Checks whether provided `token` of ASCII digits corresponds to `time`.
+    ///
+    ///Same as `verify`, but accepts raw bytes (e.g. from network buffer) without UTF-8 validation.
+    ///Only ASCII digits are accepted, regardless of `unicode` feature.
+    pub fn verify_bytes(&self, token: &[u8], time: u64) -> bool {
+        match parse_token_bytes(token) {
+            Some(token) => self.find_window_parsed(&token, time).is_some(),
+            None => false,
         }
     }
 
-    #[cfg(feature = "std")]
+    #[inline]
+    ///Checks whether provided `token` corresponds to `time`, allowing `skew` seconds of network delay
+    ///instead of `self.skew`.
+    ///
+    ///Useful to widen tolerance for a single step (e.g. enrollment) without modifying `TOTP`.
+    pub fn verify_with_skew(&self, token: &str, time: u64, skew: u8) -> bool {
+        self.find_window(token, time, skew).is_some()
+    }
+
+    #[inline]
+    ///Checks whether provided `token` corresponds to `client_time` claimed by client.
+    ///
+    ///`token` is rejected if `client_time` differs from `server_time` by more than `max_drift` seconds.
+    ///Otherwise it is verified against `client_time` same as `verify`.
+    ///
+    ///Client time cannot be trusted, so `max_drift` is the only thing preventing client from
+    ///using arbitrary time. Keep it as small as your clients' clocks allow.
+    pub fn verify_at_client_time(&self, token: &str, client_time: u64, server_time: u64, max_drift: u64) -> bool {
+        if client_time.abs_diff(server_time) > max_drift {
+            return false;
+        }
+
+        self.verify(token, client_time)
+    }
+
+    #[inline]
+    ///Checks whether provided `token` corresponds to `time`, given that `time` is within `[min_time, max_time]`.
+    ///
+    ///This is sanity check against clients claiming absurd time.
+    ///Bounds are usually derived from server time, e.g. allowing up to a few minutes in either direction
+    ///and never more than a day.
+    pub fn verify_bounded(&self, token: &str, time: u64, min_time: u64, max_time: u64) -> bool {
+        if time < min_time || time > max_time {
+            return false;
+        }
+
+        self.verify(token, time)
+    }
+
+    ///Checks whether provided `token` corresponds to `time`, returning offset of matched window.
+    ///
+    ///Offset is `0` for window containing `time`, negative for past windows and positive for future ones.
+    ///Windows are checked in the same order as by `verify`: current one first, then expanding outward within `skew`.
+    ///Aggregating offsets allows to detect clients whose clock consistently drifts.
+    pub fn verify_skew(&self, token: &str, time: u64) -> Option<i64> {
+        let matched = self.find_window(token, time, self.skew)?;
+        Some(matched as i64 - self.counter(time) as i64)
+    }
+
+    #[cfg(any(test, feature = "debug"))]
+    ///Returns every token of `digits` length accepted at `time`, together with offset of its window.
+    ///
+    ///Offsets are the same as returned by `verify_skew`, yielded from the oldest window to the newest.
+    ///Token occupies first `digits` bytes of array, while `digits` is capped at `MAX_DIGITS`.
+    ///
+    ///This is diagnostics aid only (e.g. to find out why client's code is rejected), hence it
+    ///requires `debug` feature.
+    pub fn valid_tokens(&self, time: u64, digits: u8) -> impl Iterator<Item = (i64, [u8; MAX_DIGITS as usize])> + '_ {
+        let digits = core::cmp::min(digits, MAX_DIGITS) as usize;
+        let elapsed = self.elapsed(time);
+        let counter = self.counter(time);
+        let first = elapsed.saturating_sub(self.skew as u64) / self.window;
+        let last = elapsed.saturating_add(self.skew as u64) / self.window;
+
+        (first..=last).map(move |window| {
+            let mut token = [0u8; MAX_DIGITS as usize];
+            self.hotp().generate_to(window, &mut token[..digits]);
+            (window as i64 - counter as i64, token)
+        })
+    }
+
+    ///Checks whether provided `token` corresponds to `time`, returning estimated drift of client's clock on success.
+    ///
+    ///Drift is difference in seconds between start of matched window and start of window containing `time`,
+    ///so it is `0` for exact match, negative when client's clock is behind and positive when it is ahead.
+    ///It can be accumulated to track client's clock error over time.
+    pub fn verify_with_drift_estimate(&self, token: &str, time: u64) -> Option<i64> {
+        self.verify_skew(token, time).map(|offset| offset * self.window as i64)
+    }
+
+    ///Checks whether provided `token` corresponds to `time`, accepting it only if `commit` succeeds.
+    ///
+    ///On match `commit` is invoked with counter of matched window (i.e. `time / window`), which
+    ///should be persisted to prevent replay of the same `token`.
+    ///If `commit` fails, its error is returned and `token` is not accepted.
+    ///This guarantees that accepted `token` is always recorded as used.
+    pub fn verify_transactional<E, F: FnOnce(u64) -> Result<(), E>>(&self, token: &str, time: u64, commit: F) -> Result<bool, E> {
+        match self.find_window(token, time, self.skew) {
+            Some(counter) => commit(counter).map(|_| true),
+            None => Ok(false),
+        }
+    }
+
+    ///Checks whether provided `token` corresponds to `time` or up to `back_windows` windows before it.
+    ///
+    ///Unlike `verify`, codes from future windows are never accepted.
+    ///Only a device with clock running ahead can produce such code, which is typical for attacks
+    ///that harvest codes in advance (e.g. by tampering with victim's clock).
+    ///Use it when you only need to tolerate delay between showing code and submitting it.
+    pub fn verify_past_only(&self, token: &str, time: u64, back_windows: u8) -> bool {
+        let token = match parse_token(token) {
+            Some(token) => token,
+            None => return false,
+        };
+        let digits = token.digits();
+
+        let counter = self.counter(time);
+        for window_offset in 0..=back_windows as u64 {
+            let counter = match counter.checked_sub(window_offset) {
+                Some(counter) => counter,
+                None => break,
+            };
+
+            if token.matches(self.hotp().generate_num(counter, digits)) {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    ///Checks whether provided `token` corresponds to `time`, flagging suspiciously regular codes.
+    ///
+    ///Verification is the same as `verify`, but accepted `token` is additionally checked to consist
+    ///of the same digit or trivial sequence (e.g. `000000` or `123456`).
+    ///While proper secret produces such codes occasionally, seeing them frequently for the same account
+    ///indicates degenerate (e.g. empty) secret.
+    ///
+    ///Weak codes are not rejected, it is up to caller to decide what to do with the flag.
+    pub fn verify_strict_entropy(&self, token: &str, time: u64) -> StrictVerifyResult {
+        let matched = self.verify(token, time);
+        StrictVerifyResult {
+            matched,
+            weak: matched && is_weak_token(token),
+        }
+    }
+
+    #[inline]
+    ///Checks whether provided `token` confirms enrollment of the secret at `time`.
+    ///
+    ///Intended for setup of 2FA, when user is asked to submit code right after scanning secret.
+    ///At this point clocks are assumed to be in sync, hence `skew` is ignored, but code of the
+    ///previous window is still accepted to tolerate delay of typing it.
+    pub fn confirm_setup(&self, token: &str, time: u64) -> bool {
+        self.verify_past_only(token, time, 1)
+    }
+
+    #[inline]
+    ///Checks whether provided `token` corresponds to time of provided `clock`.
+    pub fn verify_with<C: TimeSource>(&self, clock: &C, token: &str) -> bool {
+        self.verify(token, clock.now_secs())
+    }
+
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    #[inline]
+    ///Checks whether provided `token` corresponds to current system time.
+    pub fn verify_now(&self, token: &str) -> bool {
+        self.verify_with(&SystemClock, token)
+    }
+
+    ///Checks whether provided `token` corresponds to time returned by `now_fn`.
+    ///
+    ///`now_fn` is only invoked if `token` is well-formed, so time is not sampled for garbage input.
+    ///It allows to use any source of time (e.g. cached or NTP-corrected) without implementing trait.
+    ///
+    ///```
+    ///use otpshka::TOTP;
+    ///
+    ///let totp = TOTP::new(Default::default(), [72, 101, 108, 108, 111, 33, 222, 173, 190, 239]);
+    ///assert!(totp.verify_now_with("458443", || 1606206826));
+    ///assert!(!totp.verify_now_with("458443", || 30));
+    ///```
+    pub fn verify_now_with<F: FnOnce() -> u64>(&self, token: &str, now_fn: F) -> bool {
+        match parse_token(token) {
+            Some(token) => self.find_window_parsed(&token, now_fn()).is_some(),
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_test_totp_window() {
+        let input = [
+            (30, "996554"),
+            (60, "602287"),
+        ];
+
+        let secret = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+        let totp = TOTP::new(Default::default(), secret);
+
+        for (time, expected) in input.iter() {
+            let mut output = [0u8, 0, 0, 0, 0, 0];
+            assert_eq!(output.len(), expected.len());
+
+            totp.generate_to(*time, &mut output[..]);
+            let token = core::str::from_utf8(&output).expect("UTF-8 compatible output");
+            assert_eq!(token, *expected);
+            assert!(totp.verify(token, *time));
+            assert!(totp.verify(token, *time + 30));
+            assert!(totp.verify(token, *time - 1));
+        }
+    }
+
+    #[test]
+    fn should_test_totp() {
+        let input = [
+            (1606206826, "458443"),
+            (1606206917, "779542"),
+            (1606206950, "082772"),
+        ];
+
+        let secret = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+        let totp = TOTP::new(Default::default(), secret);
+
+        for (time, expected) in input.iter() {
+            let mut output = [0u8, 0, 0, 0, 0, 0];
+            assert_eq!(output.len(), expected.len());
+
+            totp.generate_to(*time, &mut output[..]);
+            let token = core::str::from_utf8(&output).expect("UTF-8 compatible output");
+            assert_eq!(token, *expected);
+            assert!(totp.verify(token, *time));
+            assert!(totp.verify(token, *time + 10));
+            assert!(totp.verify(token, *time - 10));
+        }
+    }
+
+    #[test]
+    fn should_test_totp_verify_past_only() {
+        let secret = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+        let totp = TOTP::new(Default::default(), secret);
+
+        assert!(totp.verify_past_only("996554", 30, 0));
+        assert!(totp.verify_past_only("996554", 60, 1));
+        assert!(!totp.verify_past_only("996554", 60, 0));
+        assert!(!totp.verify_past_only("996554", 90, 1));
+
+        //Future codes are rejected regardless of tolerance
+        assert!(!totp.verify_past_only("602287", 30, 0));
+        assert!(!totp.verify_past_only("602287", 30, u8::MAX));
+        assert!(!totp.verify_past_only("602287", 59, u8::MAX));
+
+        //Must not underflow near epoch
+        assert!(!totp.verify_past_only("996554", 0, u8::MAX));
+    }
+
+    #[cfg(feature = "unicode")]
+    #[test]
+    fn should_test_totp_unicode_token() {
+        let secret = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+        let totp = TOTP::new(Default::default(), secret);
+
+        assert!(totp.verify("０８２７７２", 1606206950));
+        assert!(totp.verify("٩٩٦٥٥٤", 30));
+        assert!(!totp.verify("٩٩٦٥٥٤", 90));
+    }
+
+    #[test]
+    fn should_test_totp_generate_offset() {
+        let secret = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+        let totp = TOTP::new(Default::default(), secret);
+
+        assert_eq!(totp.generate_offset(30, 0, 6), 996554);
+        assert_eq!(totp.generate_offset(60, 0, 6), 602287);
+        assert_eq!(totp.generate_offset(30, 1, 6), 602287);
+        assert_eq!(totp.generate_offset(59, 1, 6), 602287);
+        assert_eq!(totp.generate_offset(60, -1, 6), 996554);
+        assert_eq!(totp.generate_offset(1606206917, -3, 6), 458443);
+        assert_eq!(totp.generate_offset(1606206826, 3, 6), 779542);
+
+        //Saturates at the first window
+        assert_eq!(totp.generate_offset(30, -5, 6), totp.generate_num(0, 6));
+        assert_eq!(totp.generate_offset(30, i64::MIN, 6), totp.generate_num(0, 6));
+    }
+
+    #[test]
+    fn should_test_totp_verify_at_client_time() {
+        let secret = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+        let totp = TOTP::new(Default::default(), secret);
+
+        assert!(totp.verify_at_client_time("996554", 30, 30, 0));
+        assert!(totp.verify_at_client_time("996554", 45, 145, 100));
+        assert!(totp.verify_at_client_time("996554", 45, 0, 45));
+        assert!(!totp.verify_at_client_time("996554", 45, 146, 100));
+        assert!(!totp.verify_at_client_time("996554", 45, 0, 44));
+        //In bound, but wrong time
+        assert!(!totp.verify_at_client_time("996554", 90, 100, 100));
+    }
+
+    #[test]
+    fn should_test_totp_verify_cost() {
+        let secret = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+        let mut totp = TOTP::new(Default::default(), secret);
+        assert_eq!(totp.verify_cost(), 2);
+
+        totp.skew = 0;
+        assert_eq!(totp.verify_cost(), 1);
+        totp.skew = 15;
+        assert_eq!(totp.verify_cost(), 2);
+        totp.skew = 16;
+        assert_eq!(totp.verify_cost(), 3);
+        totp.skew = 30;
+        assert_eq!(totp.verify_cost(), 3);
+        totp.skew = 31;
+        assert_eq!(totp.verify_cost(), 4);
+        totp.window = 1;
+        assert_eq!(totp.verify_cost(), 63);
+
+        //Must be exact maximum of windows checked by `verify`
+        for window in [1u64, 2, 7, 30].iter() {
+            for skew in [0u8, 1, 3, 4, 15, 16, 31, 255].iter() {
+                totp.window = *window;
+                totp.skew = *skew;
+                let max = (1000..1000 + 2 * window).map(|time| {
+                    let checked = core::cell::Cell::new(0);
+                    totp.find_window_by(time, totp.skew, |_| {
+                        checked.set(checked.get() + 1);
+                        false
+                    });
+                    checked.get()
+                }).max();
+                assert_eq!(max, Some(totp.verify_cost()), "window={} skew={}", window, skew);
+            }
+        }
+
+        //Deduplication must not affect results
+        totp.window = 30;
+        totp.skew = 45;
+        assert!(totp.verify("996554", 75));
+        assert!(totp.verify("602287", 45));
+        assert!(totp.verify("602287", 134));
+        assert!(!totp.verify("602287", 135));
+        assert!(totp.verify("996554", 104));
+        assert!(!totp.verify("996554", 105));
+    }
+
+    #[test]
+    fn should_test_totp_verify_mask() {
+        let secret = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+        let mut totp = TOTP::new(Default::default(), secret);
+
+        assert_eq!(totp.verify_mask("996554", 45), 1 << 15);
+        assert_eq!(totp.verify_mask("996554", 60), 1 << 14);
+        assert_eq!(totp.verify_mask("602287", 59), 1 << 16);
+        assert_eq!(totp.verify_mask("602287", 45), 0);
+        assert_eq!(totp.verify_mask("60228", 60), 0);
+        assert_eq!(totp.verify_mask("abcdef", 60), 0);
+
+        totp.skew = 60;
+        assert_eq!(totp.verify_mask("996554", 90), 1 << 13);
+        assert_eq!(totp.verify_mask("602287", 0), 1 << 17);
+        assert_eq!(totp.verify_mask("996554", 60), 1 << 14);
+        assert_eq!(totp.verify_mask("996554", 150), 0);
+    }
+
+    #[test]
+    fn should_limit_verify_mask_to_reported_windows() {
+        let secret = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+        let mut totp = TOTP::new(Default::default(), secret);
+        totp.window = 1;
+        totp.skew = 40;
+        assert!(totp.verify_cost() > 2 * 16 + 1);
+
+        let time = 1606206950;
+        let current = totp.generate_to_array::<6>(time);
+        let current = core::str::from_utf8(&current).expect("UTF-8 compatible output");
+        assert!(totp.verify(current, time));
+        assert_eq!(totp.verify_mask(current, time) & (1 << 15), 1 << 15);
+
+        let oldest = totp.generate_to_array::<6>(time - 15);
+        let oldest = core::str::from_utf8(&oldest).expect("UTF-8 compatible output");
+        assert_eq!(totp.verify_mask(oldest, time) & 1, 1);
+        let newest = totp.generate_to_array::<6>(time + 15);
+        let newest = core::str::from_utf8(&newest).expect("UTF-8 compatible output");
+        assert_eq!(totp.verify_mask(newest, time) & (1 << 30), 1 << 30);
+
+        //Accepted by skew, but beyond reported offsets
+        let past = totp.generate_to_array::<6>(time - 20);
+        let past = core::str::from_utf8(&past).expect("UTF-8 compatible output");
+        assert!(totp.verify(past, time));
+        assert_eq!(totp.verify_mask(past, time), 0);
+    }
+
+    #[test]
+    fn should_test_totp_verify_now_with() {
+        let secret = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+        let totp = TOTP::new(Default::default(), secret);
+
+        assert!(totp.verify_now_with("082772", || 1606206950));
+        assert!(!totp.verify_now_with("082772", || 1606206826));
+        assert!(!totp.verify_now_with("08277a", || panic!("time must not be sampled for malformed token")));
+    }
+
+    #[test]
+    fn should_test_totp_confirm_setup() {
+        let secret = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+        let mut totp = TOTP::new(Default::default(), secret);
+        totp.skew = 60;
+
+        assert!(totp.confirm_setup("996554", 30));
+        assert!(totp.confirm_setup("996554", 89));
+        assert!(!totp.confirm_setup("996554", 90));
+        assert!(!totp.confirm_setup("602287", 59));
+        assert!(!totp.confirm_setup("602287", 0));
+    }
+
+    #[test]
+    fn should_test_totp_fill_recent() {
+        let secret = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+        let totp = TOTP::new(Default::default(), secret);
+
+        let recent: [[u8; 8]; 2] = totp.fill_recent(60, 6);
+        assert_eq!(recent, [*b"996554\0\0", *b"602287\0\0"]);
+
+        let recent: [[u8; 8]; 5] = totp.fill_recent(1606206950, 6);
+        assert_eq!(&recent[0][..6], b"458443");
+        assert_eq!(&recent[4][..6], b"082772");
+        let mut expected = [0u8; 8];
+        totp.generate_to(1606206950 - 30, &mut expected[..6]);
+        assert_eq!(recent[3], expected);
+
+        //Near epoch
+        let recent: [[u8; 8]; 4] = totp.fill_recent(30, 6);
+        assert_eq!(recent[0], [0; 8]);
+        assert_eq!(recent[1], [0; 8]);
+        assert_eq!(recent[3], *b"996554\0\0");
+
+        let recent: [[u8; 8]; 0] = totp.fill_recent(30, 6);
+        assert_eq!(recent.len(), 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn should_reject_fill_recent_zero_digits() {
+        let totp = TOTP::new(Default::default(), b"12345678901234567890");
+        totp.fill_recent::<2>(60, 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn should_reject_fill_recent_too_many_digits() {
+        let totp = TOTP::new(Default::default(), b"12345678901234567890");
+        totp.fill_recent::<2>(60, 9);
+    }
+
+    #[test]
+    fn should_test_totp_verify_strict_entropy() {
+        let totp = TOTP::new(Default::default(), b"12345678901234567890");
+
+        assert_eq!(totp.verify_strict_entropy("999999", 691 * 30), StrictVerifyResult { matched: true, weak: true });
+        assert_eq!(totp.verify_strict_entropy("123456", 14684 * 30), StrictVerifyResult { matched: true, weak: true });
+        assert_eq!(totp.verify_strict_entropy("000000", 691 * 30), StrictVerifyResult { matched: false, weak: false });
+        assert_eq!(totp.verify_strict_entropy("287082", 59), StrictVerifyResult { matched: true, weak: false });
+    }
+
+    #[test]
+    fn should_test_totp_verify_strict_entropy_degenerate_secret() {
+        let totp = TOTP::new(Algorithm::SHA1, [0u8; 20]);
+
+        assert_eq!(totp.generate_num(0, 6), 328482);
+        assert_eq!(totp.verify_strict_entropy("328482", 0), StrictVerifyResult { matched: true, weak: false });
+        assert_eq!(totp.generate_num(42309 * 30, 6), 555555);
+        assert_eq!(totp.verify_strict_entropy("555555", 42309 * 30), StrictVerifyResult { matched: true, weak: true });
+        assert_eq!(totp.generate_num(121388 * 30, 6), 876543);
+        assert_eq!(totp.verify_strict_entropy("876543", 121388 * 30), StrictVerifyResult { matched: true, weak: true });
+        assert_eq!(totp.verify_strict_entropy("000000", 0), StrictVerifyResult { matched: false, weak: false });
+
+        let totp = TOTP::new(Algorithm::SHA1, [0xaa; 20]);
+
+        assert_eq!(totp.generate_num(0, 6), 911105);
+        assert_eq!(totp.verify_strict_entropy("911105", 0), StrictVerifyResult { matched: true, weak: false });
+        assert_eq!(totp.generate_num(219041 * 30, 6), 0);
+        assert_eq!(totp.verify_strict_entropy("000000", 219041 * 30), StrictVerifyResult { matched: true, weak: true });
+        assert_eq!(totp.verify_strict_entropy("234567", 219041 * 30), StrictVerifyResult { matched: false, weak: false });
+    }
+
+    #[test]
+    fn should_test_totp_verify_bounded() {
+        let secret = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+        let totp = TOTP::new(Default::default(), secret);
+
+        assert!(totp.verify_bounded("458443", 1606206826, 1606206826, 1606206826));
+        assert!(totp.verify_bounded("458443", 1606206826, 1606206826 - 300, 1606206826 + 300));
+        assert!(!totp.verify_bounded("458443", 1606206826, 1606206827, 1606207126));
+        assert!(!totp.verify_bounded("458443", 1606206826, 1606206526, 1606206825));
+        assert!(!totp.verify_bounded("996554", 30, 1606206526, 1606207126));
+        assert!(!totp.verify_bounded("779542", 1606206826, 1606206526, 1606207126));
+    }
+
+    #[test]
+    fn should_test_totp_code_changed_digits() {
+        let secret = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+        let totp = TOTP::new(Default::default(), secret);
+
+        //525235 -> 634575
+        assert_eq!(totp.code_changed_digits(1606206780, 6), 5);
+        assert_eq!(totp.code_changed_digits(1606206809, 1), 0);
+        //711928 -> 779542
+        assert_eq!(totp.code_changed_digits(1606206900, 6), 5);
+        //996554 -> 602287
+        assert_eq!(totp.code_changed_digits(60, 6), 6);
+        assert_eq!(totp.code_changed_digits(29, 6), 6);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn should_test_totp_generate() {
+        let secret = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+        let totp = TOTP::new(Default::default(), secret);
+
+        assert_eq!(totp.generate(1606206950, 6), "082772");
+        assert_eq!(totp.generate(30, 6), "996554");
+        assert!(totp.verify(&totp.generate(1606206826, 8), 1606206826));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn should_test_totp_generate_safe() {
+        let secret = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+        let totp = TOTP::new(Default::default(), secret);
+
+        assert_eq!(totp.generate_safe(1606206930, 6, 5), "082772");
+        assert_eq!(totp.generate_safe(1606206954, 6, 5), "082772");
+        assert_eq!(totp.generate_safe(1606206955, 6, 5), "082772");
+        assert_eq!(totp.generate_safe(1606206956, 6, 5), "623459");
+        assert_eq!(totp.generate_safe(1606206959, 6, 5), "623459");
+        assert_eq!(totp.generate_safe(1606206959, 6, 0), "082772");
+        assert_eq!(totp.generate_safe(1606206960, 6, 5), "623459");
+    }
+
+    #[cfg(feature = "retain-secret")]
+    #[test]
+    fn should_create_totp_uri() {
+        let secret = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+        let totp = TOTP::new(Default::default(), secret);
+        assert_eq!(totp.to_uri("alice@example.com", Some("Big Corp"), 6), "otpauth://totp/Big%20Corp:alice%40example.com?secret=JBSWY3DPEHPK3PXP&issuer=Big%20Corp&algorithm=SHA1&digits=6&period=30");
+
+        let mut totp = TOTP::new(Algorithm::SHA256, secret);
+        totp.window = 60;
+        assert_eq!(totp.to_uri("alice", None, 8), "otpauth://totp/alice?secret=JBSWY3DPEHPK3PXP&algorithm=SHA256&digits=8&period=60");
+    }
+
+    #[cfg(feature = "retain-secret")]
+    #[test]
+    fn should_round_trip_totp_uri() {
+        let secret = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+        let mut totp = TOTP::new(Algorithm::SHA512, secret);
+        totp.window = 60;
+
+        let uri = totp.to_uri("alice", Some("Big Corp"), 8);
+        let (parsed, digits) = TOTP::from_uri(&uri).expect("to parse");
+        assert_eq!(digits, 8);
+        assert_eq!(parsed.window, 60);
+        assert_eq!(parsed.to_uri("alice", Some("Big Corp"), digits), uri);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn should_parse_totp_uri() {
+        let (parsed, digits) = TOTP::from_uri("otpauth://totp/alice?secret=JBSWY3DPEHPK3PXP").expect("to parse");
+        assert_eq!(digits, 6);
+        assert_eq!(parsed.window, 30);
+        assert!(parsed.verify("458443", 1606206826));
+
+        assert_eq!(TOTP::from_uri("otpauth://hotp/alice?secret=JBSWY3DPEHPK3PXP").err(), Some(crate::ParseUriError::UnsupportedType));
+    }
+
+    #[test]
+    fn should_create_totp_from_base32() {
+        let totp = TOTP::from_base32(Default::default(), "jbswy3dpehpk3pxp").expect("to decode");
+        assert!(totp.verify("458443", 1606206826));
+        assert_eq!(TOTP::from_base32(Default::default(), "JBSWY3DP!").err(), Some(crate::Base32Error::InvalidCharacter));
+    }
+
+    #[test]
+    fn should_create_totp_from_hex() {
+        let totp = TOTP::from_hex(Default::default(), "48656C6C6F21deadbeef").expect("to decode");
+        assert!(totp.verify("458443", 1606206826));
+        assert_eq!(TOTP::from_hex(Default::default(), "48656C6C6F21deadbee").err(), Some(crate::HexError::OddLength));
+    }
+
+    #[test]
+    fn should_report_ttl() {
+        let secret = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+        let mut totp = TOTP::new(Default::default(), secret);
+
+        assert_eq!(totp.ttl(0), 30);
+        assert_eq!(totp.ttl(1), 29);
+        assert_eq!(totp.ttl(29), 1);
+        assert_eq!(totp.ttl(30), 30);
+        assert_eq!(totp.ttl(1606206950), 10);
+        assert_eq!(totp.current_counter(29), 0);
+        assert_eq!(totp.current_counter(30), 1);
+        assert_eq!(totp.current_counter(1606206950), 53540231);
+
+        totp.window = 0;
+        assert_eq!(totp.ttl(1606206950), 0);
+        assert_eq!(totp.current_counter(1606206950), 0);
+    }
+
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    #[test]
+    fn should_report_ttl_now() {
+        let secret = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+        let totp = TOTP::new(Default::default(), secret);
+        let ttl = totp.ttl_now();
+        assert!((1..=30).contains(&ttl));
+    }
+
+    #[test]
+    fn should_test_totp_verify_skew() {
+        let secret = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+        let mut totp = TOTP::new(Default::default(), secret);
+
+        assert_eq!(totp.verify_skew("082772", 1606206950), Some(0));
+        assert_eq!(totp.verify_skew("082772", 1606206960), Some(-1));
+        assert_eq!(totp.verify_skew("623459", 1606206959), Some(1));
+        assert_eq!(totp.verify_skew("623459", 1606206950), None);
+        assert_eq!(totp.verify_skew("08277a", 1606206950), None);
+
+        totp.skew = 90;
+        assert_eq!(totp.verify_skew("711928", 1606206950), Some(-2));
+        assert_eq!(totp.verify_skew("623459", 1606206950), Some(1));
+    }
+
+    #[test]
+    fn should_test_totp_verify_with_drift_estimate() {
+        let secret = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+        let mut totp = TOTP::new(Default::default(), secret);
+
+        assert_eq!(totp.verify_with_drift_estimate("996554", 45), Some(0));
+        assert_eq!(totp.verify_with_drift_estimate("996554", 60), Some(-30));
+        assert_eq!(totp.verify_with_drift_estimate("602287", 59), Some(30));
+        assert_eq!(totp.verify_with_drift_estimate("602287", 45), None);
+
+        totp.skew = 60;
+        assert_eq!(totp.verify_with_drift_estimate("996554", 90), Some(-60));
+    }
+
+    #[test]
+    fn should_test_totp_verify_transactional() {
+        let secret = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+        let totp = TOTP::new(Default::default(), secret);
+
+        let mut committed = None;
+        let result = totp.verify_transactional("996554", 59, |counter| {
+            committed = Some(counter);
+            Ok::<_, ()>(())
+        });
+        assert_eq!(result, Ok(true));
+        assert_eq!(committed, Some(1));
+
+        let result = totp.verify_transactional("996554", 31, |_| Err("failed to persist"));
+        assert_eq!(result, Err("failed to persist"));
+
+        let result = totp.verify_transactional("602287", 1606206826, |_| -> Result<(), ()> {
+            panic!("commit must not be called on mismatch")
+        });
+        assert_eq!(result, Ok(false));
+    }
+
+    #[test]
+    fn should_test_totp_key_fingerprint() {
+        let secret = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+        let totp = TOTP::new(Default::default(), secret);
+
+        assert_eq!(totp.key_fingerprint(), TOTP::new(Algorithm::SHA256, secret).key_fingerprint());
+        assert_ne!(totp.key_fingerprint(), TOTP::new(Default::default(), b"12345678901234567890").key_fingerprint());
+    }
+
+    #[test]
+    fn should_test_totp_shared_key() {
+        let secret = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+        let hotp = HOTP::new(Default::default(), secret);
+        let totp = TOTP::new(Default::default(), secret);
+        let totp_ref: TotpRef<'_> = TOTP::from_hotp(&hotp);
+        let totp_ref2 = TOTP::from_hotp(&hotp);
+
+        for time in [30, 60, 1606206826].iter() {
+            assert_eq!(totp_ref.generate_num(*time, 6), totp.generate_num(*time, 6));
+            assert_eq!(totp_ref2.generate_num(*time, 6), totp.generate_num(*time, 6));
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn should_test_totp_arc_key() {
+        let secret = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+        let hotp = std::sync::Arc::new(HOTP::new(Default::default(), secret));
+        let totp = TOTP::from_hotp(hotp.clone());
+
+        assert!(totp.verify("996554", 30));
+        assert!(totp.verify("602287", 60));
+    }
+
+    #[test]
+    fn should_test_totp_generate_offset_window_computations() {
+        let secret = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+        let totp = TOTP::new(Default::default(), secret);
+        let time = 1606206917;
+
+        assert_eq!(totp.generate_offset(time, -1, 6), totp.generate_num(time - totp.window, 6));
+        assert_eq!(totp.generate_offset(time, 0, 6), totp.generate_num(time, 6));
+        assert_eq!(totp.generate_offset(time, 1, 6), totp.generate_num(time + totp.window, 6));
+    }
+
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    #[test]
+    fn should_test_totp_code_at_window_offset_now() {
+        let secret = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+        let totp = TOTP::new(Default::default(), secret);
+
+        loop {
+            let before = SystemClock.now_secs();
+            let codes = [
+                totp.code_at_window_offset_now(-1, 6),
+                totp.code_at_window_offset_now(0, 6),
+                totp.code_at_window_offset_now(1, 6),
+            ];
+            let after = SystemClock.now_secs();
+
+            //Retry if window changed in between
+            if before / totp.window != after / totp.window {
+                continue;
+            }
+
+            assert_eq!(codes[0], totp.generate_offset(before, -1, 6));
+            assert_eq!(codes[1], totp.generate_num(before, 6));
+            assert_eq!(codes[2], totp.generate_offset(before, 1, 6));
+            break;
+        }
+    }
+
+    #[test]
+    fn should_test_totp_generate_to_array() {
+        let secret = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+        let totp = TOTP::new(Default::default(), secret);
+
+        let code: [u8; 6] = totp.generate_to_array(1606206950);
+        assert_eq!(&code, b"082772");
+        let code: [u8; 8] = totp.generate_to_array(1606206826);
+        assert!(totp.verify(core::str::from_utf8(&code).expect("UTF-8 compatible output"), 1606206826));
+    }
+
+    #[test]
+    fn should_test_totp_verify_digits() {
+        let secret = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+        let totp = TOTP::new(Default::default(), secret);
+
+        let code: [u8; 8] = totp.generate_to_array(1606206950);
+        let code = core::str::from_utf8(&code).expect("UTF-8 compatible output");
+        assert!(totp.verify_digits(code, 1606206950, 8));
+        assert!(!totp.verify_digits(&code[2..], 1606206950, 8));
+        assert!(totp.verify_digits("082772", 1606206950, 6));
+        assert!(!totp.verify_digits("082772", 1606206950, 8));
+        assert!(!totp.verify_digits("08277a", 1606206950, 6));
+    }
+
+    #[test]
+    fn should_return_algorithm() {
+        let secret = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+        assert_eq!(TOTP::new(Default::default(), secret).algorithm(), Algorithm::SHA1);
+        assert_eq!(TOTP::new(Algorithm::SHA512, secret).algorithm(), Algorithm::SHA512);
+    }
+
+    #[test]
+    fn should_not_underflow_skew_at_low_time() {
+        let secret = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+        let mut totp = TOTP::new(Default::default(), secret);
+
+        assert!(totp.verify("282760", 0));
+        assert!(!totp.verify("996554", 0));
+        assert_eq!(totp.verify_skew("282760", 0), Some(0));
+
+        totp.skew = 40;
+        assert!(totp.verify("282760", 15));
+        assert!(totp.verify("996554", 15));
+        assert!(!totp.verify("602287", 15));
+
+        totp.skew = u8::MAX;
+        totp.window = 1;
+        assert!(totp.verify("282760", 0));
+        assert!(!totp.verify("000000", 0));
+    }
+
+    #[test]
+    fn should_not_overflow_skew_at_max_time() {
+        let secret = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+        let totp = TOTP::new(Default::default(), secret);
+
+        let code: [u8; 6] = totp.generate_to_array(u64::MAX);
+        assert!(totp.verify(core::str::from_utf8(&code).expect("UTF-8 compatible output"), u64::MAX));
+    }
+
+    #[test]
+    fn should_count_windows_from_t0() {
+        let secret = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+        let mut totp = TOTP::new(Default::default(), secret);
+        totp.t0 = 1000;
+
+        //Counter 1
+        assert_eq!(totp.generate_num(1030, 6), 996554);
+        assert_eq!(totp.generate_num(1059, 6), 996554);
+        assert_eq!(totp.generate_num(1060, 6), 602287);
+        assert_eq!(totp.current_counter(1030), 1);
+        assert_eq!(totp.ttl(1030), 30);
+        assert_eq!(totp.ttl(1031), 29);
+        assert!(totp.verify("996554", 1045));
+        assert!(!totp.verify("996554", 45));
+        assert_eq!(totp.verify_skew("996554", 1060), Some(-1));
+        assert_eq!(totp.verify_mask("996554", 1060), 1 << 14);
+
+        //Time before t0 is treated as t0
+        assert_eq!(totp.generate_num(0, 6), 282760);
+        assert_eq!(totp.current_counter(999), 0);
+        assert!(totp.verify("282760", 500));
+    }
+
+    #[test]
+    fn should_test_totp_steam() {
+        let secret = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+        let totp = TOTP::new(Default::default(), secret);
+
+        assert_eq!(&totp.generate_steam(1606206950), b"8TQRP");
+        assert!(totp.verify_steam("8TQRP", 1606206950));
+        assert!(totp.verify_steam("8tqrp", 1606206950));
+        assert!(totp.verify_steam("8TQRP", 1606206960));
+        assert!(totp.verify_steam("GKKC6", 1606206930));
+        assert!(!totp.verify_steam("GKKC6", 1606206950));
+        assert!(totp.verify_steam("92Q8B", 1606206959));
+        assert!(!totp.verify_steam("8TQR", 1606206950));
+        assert!(!totp.verify_steam("8TQRPP", 1606206950));
+        assert!(!totp.verify_steam("082772", 1606206950));
+        //Published vector of steamguard-cli (`steamguard` crate, `token::tests::test_generate_code`):
+        //shared secret `zvIayp3JPvtvX/QGHqsqKBk/44s=` (base64) at time 1616374841 is `2F9J5`
+        let totp = TOTP::new(Algorithm::SHA1, [206, 242, 26, 202, 157, 201, 62, 251, 111, 95, 244, 6, 30, 171, 42, 40, 25, 63, 227, 139]);
+        assert_eq!(&totp.generate_steam(1616374841), b"2F9J5");
+        assert!(totp.verify_steam("2F9J5", 1616374841));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn should_redact_totp_debug() {
+        let secret = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+        let totp = TOTP::new(Default::default(), secret);
+
+        let output = alloc::format!("{:?}", totp);
+        assert_eq!(output, "TOTP { algorithm: SHA1, window: 30, skew: 1, t0: 0, digits: 6, key: <redacted> }");
+        assert!(!output.contains("72, 101"));
+        assert!(!output.contains("Hello"));
+        assert!(!output.contains("JBSWY3DP"));
+        assert_eq!(alloc::format!("{:?}", TotpRef::from_hotp(totp.hotp())), output);
+    }
+
+    #[test]
+    fn should_verify_detailed() {
+        let totp = TOTP::new(Default::default(), [72, 101, 108, 108, 111, 33, 222, 173, 190, 239]);
+
+        assert_eq!(totp.verify_detailed("08a772", 1606206950), VerifyOutcome::Malformed);
+        assert_eq!(totp.verify_detailed("", 1606206950), VerifyOutcome::Malformed);
+        assert_eq!(totp.verify_detailed("08277200000", 1606206950), VerifyOutcome::Malformed);
+        assert!(!totp.verify("08a772", 1606206950));
+
+        //Length must match configured number of digits
+        assert_eq!(totp.verify_detailed("0827", 1606206950), VerifyOutcome::Malformed);
+        assert_eq!(totp.verify_detailed("82772", 1606206950), VerifyOutcome::Malformed);
+        assert_eq!(totp.verify_detailed("0082772", 1606206950), VerifyOutcome::Malformed);
+        for digit in 0..10u8 {
+            let token = [b'0' + digit];
+            let token = core::str::from_utf8(&token).expect("UTF-8 compatible output");
+            assert_eq!(totp.verify_detailed(token, 1606206950), VerifyOutcome::Malformed);
+        }
+        assert!(totp.verify("82772", 1606206950));
+
+        assert_eq!(totp.verify_detailed("082773", 1606206950), VerifyOutcome::Mismatch);
+        assert!(!totp.verify("082773", 1606206950));
+
+        assert_eq!(totp.verify_detailed("082772", 1606206950), VerifyOutcome::Match { offset: 0 });
+        assert!(totp.verify("082772", 1606206950));
+
+        assert_eq!(totp.verify_detailed("082772", 1606206960), VerifyOutcome::Match { offset: -1 });
+        assert_eq!(totp.verify_detailed("082772", 1606206929), VerifyOutcome::Match { offset: 1 });
+        assert_eq!(totp.verify_detailed("082772", 1606206960), VerifyOutcome::Match { offset: totp.verify_skew("082772", 1606206960).expect("to match") });
+        assert!(totp.verify_detailed("082772", 1606206929).is_match());
+        assert!(!VerifyOutcome::Mismatch.is_match());
+    }
+
+    #[test]
+    fn should_set_digits() {
+        let mut totp = TOTP::new(Default::default(), [72, 101, 108, 108, 111, 33, 222, 173, 190, 239]);
+
+        assert_eq!(totp.set_digits(0), Err(DigitsError::Zero));
+        assert_eq!(totp.set_digits(crate::MAX_DIGITS + 1), Err(DigitsError::TooMany));
+        assert_eq!(totp.digits(), 6);
+
+        assert_eq!(totp.set_digits(8), Ok(()));
+        assert_eq!(totp.digits(), 8);
+        let code = totp.generate_to_array::<8>(1606206950);
+        assert!(totp.verify_exact(core::str::from_utf8(&code).expect("UTF-8 compatible output"), 1606206950));
+        assert!(!totp.verify_exact("082772", 1606206950));
+
+        assert_eq!(totp.set_digits(crate::MAX_DIGITS), Ok(()));
+        assert_eq!(totp.digits(), crate::MAX_DIGITS);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn should_generate_with_configured_digits() {
+        struct FixedClock(u64);
+        impl TimeSource for FixedClock {
+            fn now_secs(&self) -> u64 {
+                self.0
+            }
+        }
+
+        let mut totp = TOTP::new(Default::default(), [72, 101, 108, 108, 111, 33, 222, 173, 190, 239]);
+        let clock = FixedClock(1606206950);
+        assert_eq!(totp.generate_current_with(&clock), "082772");
+
+        totp.set_digits(8).expect("valid digits");
+        let code = totp.generate_current_with(&clock);
+        assert_eq!(code.len(), 8);
+        assert!(code.ends_with("082772"));
+        assert!(totp.verify_exact(&code, clock.0));
+
+        #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+        assert_eq!(totp.generate_current().len(), 8);
+    }
+
+    #[test]
+    fn should_verify_exact_length() {
+        let totp = TOTP::new(Default::default(), [72, 101, 108, 108, 111, 33, 222, 173, 190, 239]);
+
+        assert!(totp.verify("082772", 1606206950));
+        assert!(totp.verify("82772", 1606206950));
+
+        assert!(totp.verify_exact("082772", 1606206950));
+        assert!(!totp.verify_exact("82772", 1606206950));
+        assert!(!totp.verify_exact("0082772", 1606206950));
+        assert!(!totp.verify_exact("082773", 1606206950));
+
+        let totp = TOTP::builder(Algorithm::SHA1, [72, 101, 108, 108, 111, 33, 222, 173, 190, 239]).digits(5).build().expect("to build");
+        assert!(totp.verify_exact("82772", 1606206950));
+        assert!(!totp.verify_exact("082772", 1606206950));
+    }
+
+    #[test]
+    fn should_verify_bytes() {
+        let totp = TOTP::new(Default::default(), [72, 101, 108, 108, 111, 33, 222, 173, 190, 239]);
+
+        assert!(totp.verify_bytes(b"082772", 1606206950));
+        assert!(totp.verify_bytes(b"082772", 1606206960));
+        assert!(!totp.verify_bytes(b"082772", 1606206961));
+        assert!(!totp.verify_bytes(b"182772", 1606206950));
+        assert!(!totp.verify_bytes(b"", 1606206950));
+        assert!(!totp.verify_bytes(b"08277\xff", 1606206950));
+        assert!(!totp.verify_bytes("０８２７７２".as_bytes(), 1606206950));
+        assert_eq!(totp.verify_bytes(b"458443", 1606206826), totp.verify("458443", 1606206826));
+    }
+
+    #[test]
+    fn should_verify_with_skew() {
+        let totp = TOTP::new(Default::default(), [72, 101, 108, 108, 111, 33, 222, 173, 190, 239]);
+
+        assert!(totp.verify_with_skew("082772", 1606206930, 0));
+        assert!(!totp.verify_with_skew("082772", 1606206929, 0));
+        assert!(!totp.verify("082772", 1606206989));
+        assert!(totp.verify_with_skew("082772", 1606206989, 30));
+        assert!(!totp.verify_with_skew("082772", 1606206989, 29));
+        assert!(totp.verify_with_skew("082772", 1606206901, 29));
+        assert!(!totp.verify_with_skew("08277", 1606206930, 30));
+        assert_eq!(totp.skew, 1);
+
+        assert!(totp.verify_with_skew("282760", 0, u8::MAX));
+        assert!(totp.verify_with_skew("996554", 0, 30));
+        assert!(!totp.verify_with_skew("996554", 0, 29));
+        assert!(!totp.verify_with_skew("282760", u64::MAX, u8::MAX));
+    }
+
+    #[test]
+    fn should_list_valid_tokens() {
+        let totp = TOTP::new(Default::default(), [72, 101, 108, 108, 111, 33, 222, 173, 190, 239]);
+
+        let mut tokens = totp.valid_tokens(1606206930, 6);
+        assert_eq!(tokens.next(), Some((-1, *b"779542\0\0\0\0")));
+        assert_eq!(tokens.next(), Some((0, *b"082772\0\0\0\0")));
+        assert_eq!(tokens.next(), None);
+
+        let mut tokens = totp.valid_tokens(1606206945, 6);
+        assert_eq!(tokens.next(), Some((0, *b"082772\0\0\0\0")));
+        assert_eq!(tokens.next(), None);
+
+        for (offset, token) in totp.valid_tokens(1606206959, 6) {
+            let token = core::str::from_utf8(&token[..6]).expect("UTF-8 compatible output");
+            assert_eq!(totp.verify_skew(token, 1606206959), Some(offset));
+        }
+
+        let mut tokens = totp.valid_tokens(0, 6);
+        assert_eq!(tokens.next(), Some((0, *b"282760\0\0\0\0")));
+        assert_eq!(tokens.next(), None);
+
+        let mut tokens = totp.valid_tokens(1606206930, u8::MAX);
+        assert_eq!(tokens.next().map(|(offset, token)| (offset, token.iter().all(u8::is_ascii_digit))), Some((-1, true)));
+    }
+
+    #[test]
+    fn should_use_custom_time_source() {
+        struct Rtc(u64);
+        impl TimeSource for Rtc {
+            fn now_secs(&self) -> u64 {
+                self.0
+            }
+        }
+
+        let secret = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+        let totp = TOTP::new(Default::default(), secret);
+        let clock = Rtc(1606206826);
+
+        let mut token = [0u8; 6];
+        totp.generate_to_with(&clock, &mut token[..]);
+        assert_eq!(&token, b"458443");
+        assert!(totp.verify_with(&clock, "458443"));
+        assert!(totp.verify_with(&&clock, "458443"));
+        assert!(!totp.verify_with(&Rtc(1606206950), "458443"));
+
+        assert_eq!(totp.ttl_with(&clock), 14);
+        assert_eq!(totp.code_at_window_offset_with(&clock, -1, 6), 634575);
+        assert_eq!(totp.code_at_window_offset_with(&clock, 1, 6), 304389);
+        assert_eq!(totp.code_changed_digits_with(&clock, 6), totp.code_changed_digits(1606206826, 6));
+        #[cfg(feature = "alloc")]
+        {
+            assert_eq!(totp.generate_safe_with(&clock, 6, 14), "458443");
+            assert_eq!(totp.generate_safe_with(&clock, 6, 15), "304389");
+        }
+
+        let mut verifier = crate::TotpVerifier::new(TOTP::new(Default::default(), secret));
+        assert_eq!(verifier.verify_with(&clock, "458443"), crate::VerifyResult::Ok);
+    }
+
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
     #[test]
     fn should_test_totp_now() {
         let secret = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
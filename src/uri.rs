@@ -0,0 +1,246 @@
+use core::fmt::{self, Write};
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::Algorithm;
+use crate::hex::hex_value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///Errors of parsing `otpauth` URI
+pub enum ParseUriError {
+    ///URI doesn't start with `otpauth://`
+    InvalidScheme,
+    ///OTP type is not supported (e.g. `hotp` where `totp` is expected)
+    UnsupportedType,
+    ///`secret` parameter is missing
+    MissingSecret,
+    ///`secret` parameter is specified more than once
+    DuplicateSecret,
+    ///`secret` parameter is not valid base32
+    InvalidBase32,
+    ///`algorithm` parameter is not supported
+    UnsupportedAlgorithm,
+    ///`digits` parameter is not a number within `1..=MAX_DIGITS`
+    InvalidDigits,
+    ///`period` parameter is not a positive number
+    InvalidPeriod,
+    ///`counter` parameter is missing or is not a number
+    InvalidCounter,
+}
+
+impl fmt::Display for ParseUriError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseUriError::InvalidScheme => fmt.write_str("URI scheme is not otpauth"),
+            ParseUriError::UnsupportedType => fmt.write_str("OTP type is not supported"),
+            ParseUriError::MissingSecret => fmt.write_str("Secret is missing"),
+            ParseUriError::DuplicateSecret => fmt.write_str("Secret is specified more than once"),
+            ParseUriError::InvalidBase32 => fmt.write_str("Secret is not valid base32"),
+            ParseUriError::UnsupportedAlgorithm => fmt.write_str("Algorithm is not supported"),
+            ParseUriError::InvalidDigits => fmt.write_str("Number of digits is invalid"),
+            ParseUriError::InvalidPeriod => fmt.write_str("Period is invalid"),
+            ParseUriError::InvalidCounter => fmt.write_str("Counter is missing or invalid"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseUriError {}
+
+const SCHEME: &str = "otpauth://";
+
+///Percent-encodes `text` into `out`, keeping only unreserved characters as is.
+pub(crate) fn percent_encode(text: &str, out: &mut String) {
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => out.push(byte as char),
+            byte => {
+                let _ = write!(out, "%{:02X}", byte);
+            }
+        }
+    }
+}
+
+///Splits `otpauth` URI into OTP type, label and query.
+pub(crate) fn split_uri(uri: &str) -> Result<(&str, &str, &str), ParseUriError> {
+    let rest = match uri.get(..SCHEME.len()) {
+        Some(scheme) if scheme.eq_ignore_ascii_case(SCHEME) => &uri[SCHEME.len()..],
+        _ => return Err(ParseUriError::InvalidScheme),
+    };
+
+    let (path, query) = match rest.find('?') {
+        Some(idx) => (&rest[..idx], &rest[idx + 1..]),
+        None => (rest, ""),
+    };
+
+    match path.find('/') {
+        Some(idx) => Ok((&path[..idx], &path[idx + 1..], query)),
+        None => Ok((path, "", query)),
+    }
+}
+
+///Iterates over `key=value` pairs of URI `query`, with value being percent-decoded.
+pub(crate) fn query_params(query: &str) -> impl Iterator<Item = (&str, Cow<'_, str>)> {
+    query.split('&').filter(|param| !param.is_empty()).map(|param| match param.find('=') {
+        Some(idx) => (&param[..idx], percent_decode(&param[idx + 1..])),
+        None => (param, Cow::Borrowed("")),
+    })
+}
+
+///Starts `otpauth` URI of type `kind` with label made of `issuer` and `name`.
+pub(crate) fn start_uri(kind: &str, issuer: Option<&str>, name: &str, secret: &[u8], algorithm: Algorithm, digits: u8) -> String {
+    let mut uri = String::new();
+    uri.push_str(SCHEME);
+    uri.push_str(kind);
+    uri.push('/');
+    if let Some(issuer) = issuer {
+        percent_encode(issuer, &mut uri);
+        uri.push(':');
+    }
+    percent_encode(name, &mut uri);
+    uri.push_str("?secret=");
+    uri.push_str(&crate::base32::encode(secret));
+    if let Some(issuer) = issuer {
+        uri.push_str("&issuer=");
+        percent_encode(issuer, &mut uri);
+    }
+    let _ = write!(uri, "&algorithm={}&digits={}", algorithm.name(), digits);
+    uri
+}
+
+///Decodes percent-encoded `text`, borrowing it when there is nothing to decode.
+///
+///Invalid escape sequences are kept as is, while invalid UTF-8 is replaced with `U+FFFD`.
+pub(crate) fn percent_decode(text: &str) -> Cow<'_, str> {
+    if !text.contains('%') {
+        return Cow::Borrowed(text);
+    }
+
+    let bytes = text.as_bytes();
+    let mut result = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let decoded = match bytes.get(idx..idx + 3) {
+            Some([b'%', high, low]) => match (hex_value(*high), hex_value(*low)) {
+                (Some(high), Some(low)) => Some(high << 4 | low),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        match decoded {
+            Some(byte) => {
+                result.push(byte);
+                idx += 3;
+            },
+            None => {
+                result.push(bytes[idx]);
+                idx += 1;
+            }
+        }
+    }
+
+    match String::from_utf8(result) {
+        Ok(result) => Cow::Owned(result),
+        Err(error) => Cow::Owned(String::from_utf8_lossy(error.as_bytes()).into_owned()),
+    }
+}
+
+///Looks up `:` separator, which may be percent-encoded, returning its start and end.
+fn find_label_separator(label: &str) -> Option<(usize, usize)> {
+    let bytes = label.as_bytes();
+    for idx in 0..bytes.len() {
+        match bytes[idx] {
+            b':' => return Some((idx, idx + 1)),
+            b'%' => match bytes.get(idx + 1..idx + 3) {
+                Some(code) if code.eq_ignore_ascii_case(b"3A") => return Some((idx, idx + 3)),
+                _ => continue,
+            },
+            _ => continue,
+        }
+    }
+
+    None
+}
+
+fn trim_start_spaces(mut text: &str) -> &str {
+    loop {
+        if let Some(rest) = text.strip_prefix(' ') {
+            text = rest;
+        } else if let Some(rest) = text.strip_prefix("%20") {
+            text = rest;
+        } else {
+            return text;
+        }
+    }
+}
+
+///Parses `otpauth` label into issuer and account name.
+///
+///Label has format `Issuer:Account`, where separator may be percent-encoded and followed by
+///optional spaces. Both parts are percent-decoded.
+///
+///If there is no separator, whole label is account name and issuer is `None`.
+pub fn parse_label(label: &str) -> (Option<Cow<'_, str>>, Cow<'_, str>) {
+    match find_label_separator(label) {
+        Some((start, end)) => {
+            let issuer = percent_decode(&label[..start]);
+            let account = percent_decode(trim_start_spaces(&label[end..]));
+            (Some(issuer), account)
+        },
+        None => (None, percent_decode(label)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_percent_decode() {
+        assert!(matches!(percent_decode("alice"), Cow::Borrowed("alice")));
+        assert_eq!(percent_decode("alice%40example.com"), "alice@example.com");
+        assert_eq!(percent_decode("%E3%81%82"), "あ");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("100%2"), "100%2");
+        assert_eq!(percent_decode("%zz"), "%zz");
+        assert_eq!(percent_decode("%FF"), "\u{FFFD}");
+    }
+
+    #[test]
+    fn should_percent_encode() {
+        let mut out = String::new();
+        percent_encode("Big Corp:alice@example.com~", &mut out);
+        assert_eq!(out, "Big%20Corp%3Aalice%40example.com~");
+        assert_eq!(percent_decode(&out), "Big Corp:alice@example.com~");
+    }
+
+    #[test]
+    fn should_split_uri() {
+        assert_eq!(split_uri("otpauth://totp/GitHub:alice?secret=ABC"), Ok(("totp", "GitHub:alice", "secret=ABC")));
+        assert_eq!(split_uri("OTPAUTH://hotp/alice"), Ok(("hotp", "alice", "")));
+        assert_eq!(split_uri("https://totp/alice"), Err(ParseUriError::InvalidScheme));
+        assert_eq!(split_uri("otp"), Err(ParseUriError::InvalidScheme));
+    }
+
+    #[test]
+    fn should_parse_query_params() {
+        let mut params = query_params("secret=ABC&issuer=Big%20Corp&&flag");
+        assert_eq!(params.next(), Some(("secret", "ABC".into())));
+        assert_eq!(params.next(), Some(("issuer", "Big Corp".into())));
+        assert_eq!(params.next(), Some(("flag", "".into())));
+        assert_eq!(params.next(), None);
+    }
+
+    #[test]
+    fn should_parse_label() {
+        assert_eq!(parse_label("GitHub:alice"), (Some("GitHub".into()), "alice".into()));
+        assert_eq!(parse_label("GitHub: alice"), (Some("GitHub".into()), "alice".into()));
+        assert_eq!(parse_label("alice"), (None, "alice".into()));
+        assert_eq!(parse_label("GitHub%3Aalice"), (Some("GitHub".into()), "alice".into()));
+        assert_eq!(parse_label("GitHub%3a%20%20alice"), (Some("GitHub".into()), "alice".into()));
+        assert_eq!(parse_label("Big%20Corp:alice%40example.com"), (Some("Big Corp".into()), "alice@example.com".into()));
+        assert_eq!(parse_label("a:b:c"), (Some("a".into()), "b:c".into()));
+    }
+}
@@ -0,0 +1,174 @@
+use core::borrow::Borrow;
+
+use crate::hotp::HOTP;
+use crate::token::parse_token;
+use crate::TOTP;
+use crate::time::TimeSource;
+#[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+use crate::time::SystemClock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///Result of `TotpVerifier::verify`
+pub enum VerifyResult {
+    ///Token is valid and is used for the first time.
+    Ok,
+    ///Token belongs to the window right before accepted ones, i.e. it was submitted too late.
+    Expired,
+    ///Token is valid, but its window is not newer than the window of last accepted token.
+    Replay,
+    ///Token is malformed or doesn't match.
+    Invalid,
+}
+
+///Stateful `TOTP` verifier, which rejects reuse of tokens.
+///
+///It remembers counter of the last accepted token and refuses any token, whose counter is not greater.
+///Hence at most one token is accepted per window.
+///
+///State must be shared by every place that verifies tokens of the same account, otherwise it provides no protection.
+pub struct TotpVerifier<K = HOTP> {
+    totp: TOTP<K>,
+    last_counter: Option<u64>,
+}
+
+impl<K: Borrow<HOTP>> core::fmt::Debug for TotpVerifier<K> {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        fmt.debug_struct("TotpVerifier")
+           .field("totp", &self.totp)
+           .field("last_counter", &self.last_counter)
+           .finish()
+    }
+}
+
+impl<K: Borrow<HOTP>> TotpVerifier<K> {
+    #[inline]
+    ///Creates new verifier, which hasn't accepted any token yet.
+    pub const fn new(totp: TOTP<K>) -> Self {
+        Self {
+            totp,
+            last_counter: None,
+        }
+    }
+
+    #[inline]
+    ///Creates new verifier with previously persisted counter of the last accepted token.
+    pub const fn with_last_counter(totp: TOTP<K>, last_counter: u64) -> Self {
+        Self {
+            totp,
+            last_counter: Some(last_counter),
+        }
+    }
+
+    #[inline(always)]
+    ///Returns underlying `TOTP`.
+    pub const fn totp(&self) -> &TOTP<K> {
+        &self.totp
+    }
+
+    #[inline(always)]
+    ///Returns counter of the last accepted token, which should be persisted to survive restarts.
+    pub const fn last_counter(&self) -> Option<u64> {
+        self.last_counter
+    }
+
+    ///Checks whether provided `token` corresponds to `time` and hasn't been used yet.
+    ///
+    ///On success counter of token's window is remembered.
+    pub fn verify(&mut self, token: &str, time: u64) -> VerifyResult {
+        let token = match parse_token(token) {
+            Some(token) => token,
+            None => return VerifyResult::Invalid,
+        };
+
+        match self.totp.find_window_parsed(&token, time) {
+            Some(counter) => match self.last_counter {
+                Some(last_counter) if counter <= last_counter => VerifyResult::Replay,
+                _ => {
+                    self.last_counter = Some(counter);
+                    VerifyResult::Ok
+                }
+            },
+            None => {
+                //Window before the oldest one that is within skew
+                let oldest = time.saturating_sub(self.totp.skew as u64);
+                if token.matches(self.totp.generate_offset(oldest, -1, token.digits())) {
+                    VerifyResult::Expired
+                } else {
+                    VerifyResult::Invalid
+                }
+            }
+        }
+    }
+
+    #[inline]
+    ///Checks whether provided `token` corresponds to time of provided `clock` and hasn't been used yet.
+    ///
+    ///Refer to `verify` for details.
+    pub fn verify_with<C: TimeSource>(&mut self, clock: &C, token: &str) -> VerifyResult {
+        self.verify(token, clock.now_secs())
+    }
+
+    #[cfg(all(feature = "std", not(all(target_arch = "wasm32", target_os = "unknown"))))]
+    #[inline]
+    ///Checks whether provided `token` corresponds to current system time and hasn't been used yet.
+    ///
+    ///Refer to `verify` for details.
+    pub fn verify_now(&mut self, token: &str) -> VerifyResult {
+        self.verify_with(&SystemClock, token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: [u8; 10] = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+
+    #[test]
+    fn should_verify_with_time_source() {
+        struct FixedClock(u64);
+        impl TimeSource for FixedClock {
+            fn now_secs(&self) -> u64 {
+                self.0
+            }
+        }
+
+        let mut verifier = TotpVerifier::new(TOTP::new(Default::default(), SECRET));
+        assert_eq!(verifier.verify_with(&FixedClock(1606206826), "458443"), VerifyResult::Ok);
+        assert_eq!(verifier.verify_with(&FixedClock(1606206827), "458443"), VerifyResult::Replay);
+        assert_eq!(verifier.verify_with(&FixedClock(1606206950), "082772"), VerifyResult::Ok);
+    }
+
+    #[test]
+    fn should_reject_replayed_token() {
+        let mut verifier = TotpVerifier::new(TOTP::new(Default::default(), SECRET));
+        assert_eq!(verifier.last_counter(), None);
+
+        assert_eq!(verifier.verify("458443", 1606206826), VerifyResult::Ok);
+        assert_eq!(verifier.last_counter(), Some(53540227));
+        assert_eq!(verifier.verify("458443", 1606206827), VerifyResult::Replay);
+
+        assert_eq!(verifier.verify("779542", 1606206917), VerifyResult::Ok);
+        assert_eq!(verifier.verify("779542", 1606206917), VerifyResult::Replay);
+        //Previous window is still within skew, but it is older than last accepted
+        assert_eq!(verifier.verify("711928", 1606206900), VerifyResult::Replay);
+
+        assert_eq!(verifier.verify("082772", 1606206950), VerifyResult::Ok);
+        assert_eq!(verifier.last_counter(), Some(53540231));
+    }
+
+    #[test]
+    fn should_distinguish_expired_and_invalid_token() {
+        let mut verifier = TotpVerifier::with_last_counter(TOTP::new(Default::default(), SECRET), 53540220);
+
+        assert_eq!(verifier.verify("779542", 1606206950), VerifyResult::Expired);
+        assert_eq!(verifier.verify("711928", 1606206950), VerifyResult::Invalid);
+        assert_eq!(verifier.verify("082773", 1606206950), VerifyResult::Invalid);
+        assert_eq!(verifier.verify("08277a", 1606206950), VerifyResult::Invalid);
+        assert_eq!(verifier.verify("", 1606206950), VerifyResult::Invalid);
+        assert_eq!(verifier.last_counter(), Some(53540220));
+
+        assert_eq!(verifier.verify("996554", 1), VerifyResult::Invalid);
+        assert_eq!(verifier.verify("282760", 1), VerifyResult::Replay);
+    }
+}
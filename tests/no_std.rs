@@ -0,0 +1,37 @@
+//!Exercises public API without `std`, ensuring `no_std` contract holds.
+#![no_std]
+
+use otpshka::{max_code, Algorithm, HOTP, TOTP, MAX_DIGITS};
+
+const SECRET: [u8; 10] = [72, 101, 108, 108, 111, 33, 222, 173, 190, 239];
+const MAX_6_DIGITS: u32 = max_code(6);
+const MAX_CODE: u32 = max_code(MAX_DIGITS);
+
+#[test]
+fn should_evaluate_helpers_in_const_context() {
+    assert_eq!(MAX_6_DIGITS, 999_999);
+    assert_eq!(MAX_CODE, 0x7fff_ffff);
+}
+
+#[test]
+fn should_generate_and_verify_hotp() {
+    let hotp = HOTP::new(Algorithm::SHA1, b"12345678901234567890");
+
+    let mut output = [0u8; 6];
+    hotp.generate_to(0, &mut output[..]);
+    assert_eq!(&output, b"755224");
+    assert!(hotp.verify("755224", 0));
+    assert!(hotp.generate_num(0, 6) <= MAX_6_DIGITS);
+}
+
+#[test]
+fn should_generate_and_verify_totp() {
+    let totp = TOTP::new(Algorithm::SHA1, SECRET);
+
+    let mut output = [0u8; 6];
+    totp.generate_to(1606206950, &mut output[..]);
+    assert_eq!(&output, b"082772");
+    assert!(totp.verify("082772", 1606206950));
+    assert!(!totp.verify("082772", 30));
+    assert_eq!(totp.verify_cost(), 2);
+}